@@ -0,0 +1,403 @@
+use crate::ballistics::{
+    Altitudes, DragModel, DragTrajectory, Ground, GroundImpact, VacuumTrajectory, integrate_with_drag,
+    integrate_with_drag_spherical,
+};
+use crate::geo::{
+    EARTH_RADIUS, Geodesic, final_bearing, haversine_distance, initial_bearing, midpoint, vincenty_inverse,
+};
+use crate::monte_carlo::{self, MonteCarloResult};
+use crate::scenario::{EarthModel, Scenario};
+use serde::Serialize;
+
+/// Whether a range tier comes from a published figure or from the models.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TierKind {
+    Declared,
+    Physics,
+}
+
+/// One candidate maximum range the claim is checked against.
+#[derive(Debug, Clone, Serialize)]
+pub struct RangeTier {
+    pub name: String,
+    pub kind: TierKind,
+    #[serde(rename = "range_m")]
+    pub range: f64,
+    /// Source key, see `WeaponSpecs::references`.
+    pub source: String,
+    #[serde(rename = "shortfall_m")]
+    pub shortfall: f64,
+    pub multiplier: f64,
+    /// False for a tier shown for comparison only, such as the air-drag
+    /// model with an unsourced drag curve.
+    pub in_verdict: bool,
+}
+
+impl RangeTier {
+    fn new(name: String, kind: TierKind, range: f64, source: &str, distance: f64) -> Self {
+        RangeTier {
+            name,
+            kind,
+            range,
+            source: source.to_string(),
+            shortfall: distance - range,
+            multiplier: distance / range,
+            in_verdict: true,
+        }
+    }
+
+    pub fn reaches(&self) -> bool {
+        self.shortfall <= 0.0
+    }
+}
+
+/// Overall verdict, from the least to the most generous tier that reaches.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    /// Within the operational range.
+    WithinOperational,
+    /// Only within the published maximum or an extended-range variant.
+    WithinDeclared,
+    /// Beyond every declared range, reachable only in the physics models.
+    PhysicsOnly,
+    /// Beyond every tier, including the vacuum upper bound.
+    Impossible,
+}
+
+impl Verdict {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Verdict::WithinOperational => "POSSIBLE",
+            Verdict::WithinDeclared => "EXTREME RANGE ONLY",
+            Verdict::PhysicsOnly => "BEYOND EVERY DECLARED RANGE",
+            Verdict::Impossible => "IMPOSSIBLE",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    pub actual_distance: f64,
+    pub initial_bearing: f64,
+    pub final_bearing: f64,
+    pub midpoint: (f64, f64),
+    pub ellipsoidal: Geodesic,
+    pub trajectory: VacuumTrajectory,
+    pub t_flight: f64,
+    pub range_theoretical: f64,
+    pub max_h: f64,
+    /// Where the vacuum trajectory meets the terrain, or the target
+    /// altitude without terrain.
+    pub vacuum_impact: GroundImpact,
+    pub earth_model: EarthModel,
+    /// Launch site and target ground heights above sea level.
+    pub altitudes: Altitudes,
+    pub drag_flat: DragTrajectory,
+    pub drag_spherical: DragTrajectory,
+    pub range_shortfall: f64,
+    pub range_multiplier: f64,
+    /// Every range the claim is checked against, shortest first.
+    pub tiers: Vec<RangeTier>,
+    /// Sampled site and weapon uncertainties, `None` when the scenario has
+    /// none and the verdict is deterministic.
+    pub monte_carlo: Option<MonteCarloResult>,
+}
+
+impl Analysis {
+    pub fn new(scenario: &Scenario) -> Result<Self, String> {
+        let launch = &scenario.launch;
+        let target = &scenario.target;
+        let actual_distance = haversine_distance(launch.lat, launch.lon, target.lat, target.lon);
+        let initial_bearing = initial_bearing(launch.lat, launch.lon, target.lat, target.lon);
+        let final_bearing = final_bearing(launch.lat, launch.lon, target.lat, target.lon);
+        let midpoint = midpoint(launch.lat, launch.lon, target.lat, target.lon);
+        let ellipsoidal = vincenty_inverse(launch.lat, launch.lon, target.lat, target.lon);
+
+        let altitudes = scenario.altitudes();
+        let trajectory = VacuumTrajectory::new(
+            scenario.weapon.muzzle_velocity,
+            scenario.physics.launch_angle,
+            scenario.physics.gravity,
+            altitudes,
+        );
+
+        let ground = Ground {
+            altitudes,
+            terrain: scenario.terrain_profile.as_ref(),
+        };
+        let vacuum_impact = trajectory.ground_impact(&ground, scenario.physics.time_step);
+        let drag_model = DragModel::new(
+            scenario.weapon.projectile_mass,
+            scenario.weapon.calibre,
+            scenario.weapon.drag_coefficients.clone(),
+            scenario.weapon.motor,
+        );
+        let drag_flat = integrate_with_drag(
+            scenario.weapon.muzzle_velocity,
+            scenario.physics.launch_angle,
+            scenario.physics.gravity,
+            &drag_model,
+            &scenario.atmosphere,
+            &ground,
+            scenario.physics.time_step,
+        );
+        let drag_spherical = integrate_with_drag_spherical(
+            scenario.weapon.muzzle_velocity,
+            scenario.physics.launch_angle,
+            scenario.physics.gravity,
+            &drag_model,
+            &scenario.atmosphere,
+            EARTH_RADIUS,
+            &ground,
+            scenario.physics.time_step,
+        );
+
+        let range_shortfall = actual_distance - scenario.weapon.max_range_operational;
+        let range_multiplier = actual_distance / scenario.weapon.max_range_operational;
+
+        let weapon = &scenario.weapon;
+        let mut tiers = vec![
+            RangeTier::new(
+                "Operational (typical)".to_string(),
+                TierKind::Declared,
+                weapon.max_range_operational,
+                weapon.source("max_range_operational"),
+                actual_distance,
+            ),
+            RangeTier::new(
+                "Published maximum".to_string(),
+                TierKind::Declared,
+                weapon.max_range_45deg,
+                weapon.source("max_range_45deg"),
+                actual_distance,
+            ),
+        ];
+        for extended in &weapon.extended_ranges {
+            tiers.push(RangeTier::new(
+                format!("Extended: {}", extended.name),
+                TierKind::Declared,
+                extended.range,
+                &extended.source,
+                actual_distance,
+            ));
+        }
+        let drag = match scenario.physics.earth_model {
+            EarthModel::Flat => &drag_flat,
+            EarthModel::Spherical => &drag_spherical,
+        };
+        tiers.push(RangeTier {
+            in_verdict: weapon.drag_model_is_sourced(),
+            ..RangeTier::new(
+                format!("Air-drag model ({} Earth)", scenario.physics.earth_model.name()),
+                TierKind::Physics,
+                drag.range,
+                weapon.source("drag_coefficients"),
+                actual_distance,
+            )
+        });
+        // The longest vacuum range at any launch angle between the site
+        // altitudes, (v0 / g)·√(v0² + 2g·Δh); v0² / g on level ground.
+        let vacuum_bound = VacuumTrajectory::optimal(weapon.muzzle_velocity, scenario.physics.gravity, altitudes);
+        tiers.push(RangeTier::new(
+            format!("Vacuum upper bound ({:.0} deg)", vacuum_bound.theta.to_degrees()),
+            TierKind::Physics,
+            vacuum_bound.range(),
+            weapon.source("muzzle_velocity"),
+            actual_distance,
+        ));
+        tiers.sort_by(|a, b| a.range.total_cmp(&b.range));
+
+        let monte_carlo = if scenario.has_uncertainty() {
+            Some(monte_carlo::run(scenario)?)
+        } else {
+            None
+        };
+
+        Ok(Analysis {
+            actual_distance,
+            initial_bearing,
+            final_bearing,
+            midpoint,
+            ellipsoidal,
+            trajectory,
+            t_flight: trajectory.flight_time(),
+            range_theoretical: trajectory.range(),
+            max_h: trajectory.max_height(),
+            vacuum_impact,
+            earth_model: scenario.physics.earth_model,
+            altitudes,
+            drag_flat,
+            drag_spherical,
+            range_shortfall,
+            range_multiplier,
+            tiers,
+            monte_carlo,
+        })
+    }
+
+    /// Ellipsoidal minus spherical distance, in meters.
+    pub fn ellipsoidal_difference(&self) -> f64 {
+        self.ellipsoidal.distance - self.actual_distance
+    }
+
+    /// Drag trajectory for the scenario's Earth model.
+    pub fn drag(&self) -> &DragTrajectory {
+        match self.earth_model {
+            EarthModel::Flat => &self.drag_flat,
+            EarthModel::Spherical => &self.drag_spherical,
+        }
+    }
+
+    /// Ground-arc range on a round Earth minus flat-Earth range, in meters.
+    pub fn curvature_difference(&self) -> f64 {
+        self.drag_spherical.range - self.drag_flat.range
+    }
+
+    /// Vacuum range between the site altitudes minus the range on level
+    /// ground, in meters.
+    pub fn altitude_difference(&self) -> f64 {
+        self.range_theoretical - self.trajectory.level_range()
+    }
+
+    /// Fraction of the vacuum range lost to air resistance.
+    pub fn drag_range_loss(&self) -> f64 {
+        1.0 - self.drag().range / self.range_theoretical
+    }
+
+    pub fn is_possible(&self) -> bool {
+        self.range_shortfall <= 0.0
+    }
+
+    /// Tiers the verdict is decided on, shortest first.
+    pub fn verdict_tiers(&self) -> impl Iterator<Item = &RangeTier> {
+        self.tiers.iter().filter(|t| t.in_verdict)
+    }
+
+    /// The longest range tier counted in the verdict, the most generous
+    /// assumption.
+    pub fn most_generous_tier(&self) -> &RangeTier {
+        self.verdict_tiers().last().expect("analysis always has range tiers")
+    }
+
+    /// The shortest tier counted in the verdict that reaches the target.
+    pub fn deciding_tier(&self) -> Option<&RangeTier> {
+        self.verdict_tiers().find(|t| t.reaches())
+    }
+
+    /// The verdict with the tier that decides it, e.g. `WITHIN DECLARED
+    /// RANGE (published maximum)`, for titles and banners.
+    pub fn verdict_headline(&self) -> String {
+        let deciding: String = self
+            .deciding_tier()
+            .map(|t| {
+                let mut chars = t.name.chars();
+                chars.next().map(|c| c.to_lowercase().chain(chars).collect()).unwrap_or_default()
+            })
+            .unwrap_or_default();
+        match self.verdict() {
+            Verdict::WithinOperational => "WITHIN OPERATIONAL RANGE".to_string(),
+            Verdict::WithinDeclared => format!("WITHIN DECLARED RANGE ({})", deciding),
+            Verdict::PhysicsOnly => format!("BEYOND EVERY DECLARED RANGE (only {})", deciding),
+            Verdict::Impossible => "BEYOND EVERY RANGE, INCLUDING THE VACUUM BOUND".to_string(),
+        }
+    }
+
+    pub fn verdict(&self) -> Verdict {
+        if self.is_possible() {
+            Verdict::WithinOperational
+        } else if self.verdict_tiers().any(|t| t.kind == TierKind::Declared && t.reaches()) {
+            Verdict::WithinDeclared
+        } else if self.deciding_tier().is_some() {
+            Verdict::PhysicsOnly
+        } else {
+            Verdict::Impossible
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::destination_point;
+
+    /// The default scenario with the target `distance` meters east of the
+    /// launch point and no uncertainty.
+    fn scenario_at(distance: f64) -> Scenario {
+        let mut scenario = Scenario::default();
+        let (lat, lon) = destination_point(scenario.launch.lat, scenario.launch.lon, 90.0, distance);
+        scenario.target.lat = lat;
+        scenario.target.lon = lon;
+        scenario.launch.uncertainty = 0.0;
+        scenario.target.uncertainty = 0.0;
+        scenario.uncertainty.max_range_operational = None;
+        scenario.uncertainty.muzzle_velocity = None;
+        scenario
+    }
+
+    #[test]
+    fn verdict_follows_the_shortest_reaching_tier() {
+        let cases = [
+            (10000.0, Verdict::WithinOperational, "Operational (typical)"),
+            (18000.0, Verdict::WithinDeclared, "Published maximum"),
+            (35000.0, Verdict::WithinDeclared, "Extended: 9M521 rocket"),
+            (45000.0, Verdict::PhysicsOnly, "Vacuum upper bound (45 deg)"),
+        ];
+        for (distance, verdict, tier) in cases {
+            let analysis = Analysis::new(&scenario_at(distance)).unwrap();
+            assert_eq!(analysis.verdict(), verdict, "{} m", distance);
+            assert_eq!(analysis.deciding_tier().unwrap().name, tier, "{} m", distance);
+            assert!(analysis.monte_carlo.is_none());
+        }
+
+        let analysis = Analysis::new(&scenario_at(60000.0)).unwrap();
+        assert_eq!(analysis.verdict(), Verdict::Impossible);
+        assert!(analysis.deciding_tier().is_none());
+    }
+
+    #[test]
+    fn headline_names_the_deciding_tier() {
+        let headline = |distance| Analysis::new(&scenario_at(distance)).unwrap().verdict_headline();
+        assert_eq!(headline(10000.0), "WITHIN OPERATIONAL RANGE");
+        assert_eq!(headline(35000.0), "WITHIN DECLARED RANGE (extended: 9M521 rocket)");
+        assert_eq!(headline(18000.0), "WITHIN DECLARED RANGE (published maximum)");
+        assert_eq!(headline(45000.0), "BEYOND EVERY DECLARED RANGE (only vacuum upper bound (45 deg))");
+        assert_eq!(headline(60000.0), "BEYOND EVERY RANGE, INCLUDING THE VACUUM BOUND");
+    }
+
+    #[test]
+    fn unsourced_drag_tier_is_left_out_of_the_verdict() {
+        let drag_tier = |analysis: &Analysis| {
+            analysis
+                .tiers
+                .iter()
+                .find(|t| t.name.starts_with("Air-drag"))
+                .cloned()
+                .unwrap()
+        };
+
+        let scenario = scenario_at(24000.0);
+        assert!(!scenario.weapon.is_sourced("drag_coefficients"));
+        let analysis = Analysis::new(&scenario).unwrap();
+        assert!(!drag_tier(&analysis).in_verdict);
+        assert!(analysis.verdict_tiers().all(|t| !t.name.starts_with("Air-drag")));
+        assert!(analysis.most_generous_tier().name.starts_with("Vacuum"));
+
+        // A sourced drag curve is not enough while the motor is estimated.
+        let mut scenario = scenario;
+        scenario
+            .weapon
+            .sources
+            .insert("drag_coefficients".to_string(), "janes_iw".to_string());
+        let analysis = Analysis::new(&scenario).unwrap();
+        assert!(scenario.weapon.motor.is_some());
+        assert!(!drag_tier(&analysis).in_verdict);
+
+        for field in ["burn_time", "propellant_mass", "thrust"] {
+            scenario.weapon.sources.insert(field.to_string(), "janes_iw".to_string());
+        }
+        let analysis = Analysis::new(&scenario).unwrap();
+        assert!(drag_tier(&analysis).in_verdict);
+    }
+}
@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+
+const GAS_CONSTANT_AIR: f64 = 287.05287;
+const HEAT_CAPACITY_RATIO: f64 = 1.4;
+const STANDARD_GRAVITY: f64 = 9.80665;
+const EARTH_RADIUS_GEOPOTENTIAL: f64 = 6356766.0;
+
+/// ICAO standard atmosphere layers: base geopotential altitude (m) and
+/// temperature lapse rate (K/m), up to the mesopause at 84,852 m.
+const LAYERS: [(f64, f64); 7] = [
+    (0.0, -0.0065),
+    (11000.0, 0.0),
+    (20000.0, 0.001),
+    (32000.0, 0.0028),
+    (47000.0, 0.0),
+    (51000.0, -0.0028),
+    (71000.0, -0.002),
+];
+const TOP_OF_MODEL: f64 = 84852.0;
+
+/// International Standard Atmosphere, optionally shifted to non-standard
+/// sea-level temperature and pressure.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct Atmosphere {
+    /// Kelvin
+    pub sea_level_temperature: f64,
+    /// Pascal
+    pub sea_level_pressure: f64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct AirState {
+    pub temperature: f64,
+    pub pressure: f64,
+    pub density: f64,
+    pub speed_of_sound: f64,
+}
+
+impl Default for Atmosphere {
+    fn default() -> Self {
+        Atmosphere {
+            sea_level_temperature: 288.15,
+            sea_level_pressure: 101325.0,
+        }
+    }
+}
+
+impl Atmosphere {
+    /// Air properties at a geometric altitude in meters. Altitudes below sea
+    /// level extrapolate the troposphere; above the model top the values are
+    /// held constant.
+    pub fn at(&self, altitude: f64) -> AirState {
+        let h = geopotential_altitude(altitude).min(TOP_OF_MODEL);
+
+        let mut base_temperature = self.sea_level_temperature;
+        let mut base_pressure = self.sea_level_pressure;
+        let mut layer = LAYERS[0];
+        for (i, &(base, lapse)) in LAYERS.iter().enumerate() {
+            layer = (base, lapse);
+            let next_base = LAYERS.get(i + 1).map_or(f64::INFINITY, |l| l.0);
+            if h < next_base {
+                break;
+            }
+            let dh = next_base - base;
+            let top_temperature = base_temperature + lapse * dh;
+            base_pressure = layer_pressure(base_pressure, base_temperature, lapse, dh);
+            base_temperature = top_temperature;
+        }
+
+        let (base, lapse) = layer;
+        let temperature = base_temperature + lapse * (h - base);
+        let pressure = layer_pressure(base_pressure, base_temperature, lapse, h - base);
+
+        AirState {
+            temperature,
+            pressure,
+            density: pressure / (GAS_CONSTANT_AIR * temperature),
+            speed_of_sound: (HEAT_CAPACITY_RATIO * GAS_CONSTANT_AIR * temperature).sqrt(),
+        }
+    }
+
+    pub fn density(&self, altitude: f64) -> f64 {
+        self.at(altitude).density
+    }
+
+    pub fn speed_of_sound(&self, altitude: f64) -> f64 {
+        self.at(altitude).speed_of_sound
+    }
+}
+
+fn geopotential_altitude(altitude: f64) -> f64 {
+    EARTH_RADIUS_GEOPOTENTIAL * altitude / (EARTH_RADIUS_GEOPOTENTIAL + altitude)
+}
+
+/// Hydrostatic pressure `dh` above a layer base.
+fn layer_pressure(base_pressure: f64, base_temperature: f64, lapse: f64, dh: f64) -> f64 {
+    if lapse == 0.0 {
+        base_pressure * (-STANDARD_GRAVITY * dh / (GAS_CONSTANT_AIR * base_temperature)).exp()
+    } else {
+        base_pressure
+            * (base_temperature / (base_temperature + lapse * dh))
+                .powf(STANDARD_GRAVITY / (GAS_CONSTANT_AIR * lapse))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Geometric altitude of a geopotential altitude, as tabulated by ICAO.
+    fn geometric(geopotential: f64) -> f64 {
+        EARTH_RADIUS_GEOPOTENTIAL * geopotential / (EARTH_RADIUS_GEOPOTENTIAL - geopotential)
+    }
+
+    fn assert_relative(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            ((actual - expected) / expected).abs() < tolerance,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn icao_table_at_sea_level() {
+        let air = Atmosphere::default().at(0.0);
+        assert_eq!(air.temperature, 288.15);
+        assert_eq!(air.pressure, 101325.0);
+        assert_relative(air.density, 1.2250, 1e-4);
+        assert_relative(air.speed_of_sound, 340.294, 1e-5);
+    }
+
+    #[test]
+    fn icao_table_at_the_tropopause() {
+        let air = Atmosphere::default().at(geometric(11000.0));
+        assert_relative(air.temperature, 216.65, 1e-9);
+        assert_relative(air.pressure, 22632.06, 1e-5);
+        assert_relative(air.density, 0.363918, 1e-5);
+        assert_relative(air.speed_of_sound, 295.070, 1e-5);
+    }
+
+    #[test]
+    fn icao_table_at_20_km() {
+        let air = Atmosphere::default().at(geometric(20000.0));
+        assert_relative(air.temperature, 216.65, 1e-9);
+        assert_relative(air.pressure, 5474.889, 1e-5);
+        assert_relative(air.density, 0.0880349, 1e-5);
+    }
+
+    #[test]
+    fn hot_day_lowers_sea_level_density() {
+        let hot = Atmosphere {
+            sea_level_temperature: 308.15,
+            ..Atmosphere::default()
+        };
+        let air = hot.at(0.0);
+        assert_relative(air.density, 101325.0 / (GAS_CONSTANT_AIR * 308.15), 1e-12);
+        assert!(air.density < Atmosphere::default().density(0.0));
+    }
+}
@@ -0,0 +1,782 @@
+use crate::atmosphere::Atmosphere;
+use crate::terrain::TerrainProfile;
+
+/// Closed-form projectile motion in a vacuum, from the launch altitude to
+/// the impact altitude.
+#[derive(Debug, Clone, Copy)]
+pub struct VacuumTrajectory {
+    pub v0: f64,
+    pub theta: f64,
+    pub g: f64,
+    pub altitudes: Altitudes,
+}
+
+impl VacuumTrajectory {
+    pub fn new(v0: f64, launch_angle_deg: f64, g: f64, altitudes: Altitudes) -> Self {
+        VacuumTrajectory {
+            v0,
+            theta: launch_angle_deg.to_radians(),
+            g,
+            altitudes,
+        }
+    }
+
+    /// The launch angle with the longest range between `altitudes`,
+    /// `atan(v0 / √(v0² + 2g·Δh))`: 45° on level ground, flatter when
+    /// firing downhill.
+    pub fn optimal(v0: f64, g: f64, altitudes: Altitudes) -> Self {
+        let drop = altitudes.launch - altitudes.impact;
+        let theta = v0.atan2((v0 * v0 + 2.0 * g * drop).max(0.0).sqrt());
+        VacuumTrajectory::new(v0, theta.to_degrees(), g, altitudes)
+    }
+
+    /// Height of the launch site above the impact point, negative when
+    /// firing uphill.
+    pub fn drop(&self) -> f64 {
+        self.altitudes.launch - self.altitudes.impact
+    }
+
+    /// Time to come down to the impact altitude,
+    /// `(v0·sin θ + √((v0·sin θ)² + 2g·Δh)) / g`. An impact altitude above
+    /// the apex is never reached, and the flight ends at the apex as in
+    /// the drag model.
+    pub fn flight_time(&self) -> f64 {
+        let vy = self.v0 * self.theta.sin();
+        (vy + (vy * vy + 2.0 * self.g * self.drop()).max(0.0).sqrt()) / self.g
+    }
+
+    pub fn range(&self) -> f64 {
+        self.v0 * self.theta.cos() * self.flight_time()
+    }
+
+    /// `v0²·sin(2θ) / g`, the range with launch and impact at the same
+    /// altitude.
+    pub fn level_range(&self) -> f64 {
+        (self.v0.powi(2) * (2.0 * self.theta).sin()) / self.g
+    }
+
+    /// Whether the impact altitude is at or below the apex.
+    pub fn reaches_impact_altitude(&self) -> bool {
+        self.max_height() >= -self.drop()
+    }
+
+    /// Apex above the launch site.
+    pub fn max_height(&self) -> f64 {
+        (self.v0.powi(2) * self.theta.sin().powi(2)) / (2.0 * self.g)
+    }
+
+    /// Downrange distance and height relative to the launch point.
+    pub fn position(&self, t: f64) -> (f64, f64) {
+        let x = self.v0 * self.theta.cos() * t;
+        let y = self.v0 * self.theta.sin() * t - 0.5 * self.g * t.powi(2);
+        (x, y)
+    }
+
+    /// `n` points evenly spaced in time from launch to impact.
+    pub fn sample(&self, n: usize) -> Vec<(f64, f64)> {
+        self.sample_until(n, self.flight_time())
+    }
+
+    /// `n` points evenly spaced in time from launch to `t_end`.
+    pub fn sample_until(&self, n: usize, t_end: f64) -> Vec<(f64, f64)> {
+        (0..n)
+            .map(|i| self.position(t_end * (i as f64) / (n as f64 - 1.0)))
+            .collect()
+    }
+
+    /// Where the path first meets `ground`, scanned in steps of `dt` and
+    /// refined by bisection.
+    pub fn ground_impact(&self, ground: &Ground, dt: f64) -> GroundImpact {
+        let launch = self.altitudes.launch;
+        let position = |t: f64| {
+            let (x, y) = self.position(t);
+            (x, y + launch)
+        };
+        let clearance = |t: f64| {
+            let (x, altitude) = position(t);
+            altitude - ground.height(x)
+        };
+
+        let mut t = 0.0;
+        while t < MAX_FLIGHT_TIME {
+            let next = t + dt;
+            let (_, altitude) = position(t);
+            let (_, next_altitude) = position(next);
+            if hits_ground(clearance(t), clearance(next), next_altitude < altitude) {
+                let time = if clearance(t) > 0.0 { bisect(t, next, clearance) } else { t };
+                let (range, elevation) = position(time);
+                // Keep following the free path while it could still clear
+                // the ground ahead.
+                let lowest = ground.lowest();
+                let mut unblocked_range = None;
+                let (mut t, mut emerged) = (next, false);
+                while position(t).1 >= lowest && t < MAX_FLIGHT_TIME {
+                    let next = t + dt;
+                    emerged |= clearance(t) > 0.0;
+                    if emerged && clearance(next) <= 0.0 {
+                        unblocked_range = Some(position(bisect(t, next, clearance)).0);
+                        break;
+                    }
+                    t = next;
+                }
+                return GroundImpact {
+                    time,
+                    range,
+                    elevation,
+                    unblocked_range,
+                };
+            }
+            t = next;
+        }
+        let (range, elevation) = position(t);
+        GroundImpact {
+            time: t,
+            range,
+            elevation,
+            unblocked_range: None,
+        }
+    }
+}
+
+/// Where a trajectory first meets the ground.
+#[derive(Debug, Clone, Copy)]
+pub struct GroundImpact {
+    pub time: f64,
+    /// Downrange distance from the launch site.
+    pub range: f64,
+    /// Height above sea level of the ground at the impact.
+    pub elevation: f64,
+    /// Where the path would have come down if the ground it met were not
+    /// there, when it is a ridge that the path would clear again beyond.
+    pub unblocked_range: Option<f64>,
+}
+
+impl GroundImpact {
+    /// Whether an intervening ridge stops the path short of where it would
+    /// otherwise come down.
+    pub fn blocked(&self) -> bool {
+        self.unblocked_range.is_some()
+    }
+}
+
+/// Whether a step from `clearance` to `next_clearance` above the ground
+/// ends the flight: on crossing the ground, or when descending while still
+/// below it, as for a target higher than the apex.
+fn hits_ground(clearance: f64, next_clearance: f64, descending: bool) -> bool {
+    next_clearance < 0.0 && (clearance >= 0.0 || descending)
+}
+
+/// Parameter in `[above, below]` where `clearance` crosses zero, given it
+/// is positive at `above` and not at `below`. The result is on the positive
+/// side, so the impact point never lies under the ground.
+fn bisect(mut above: f64, mut below: f64, clearance: impl Fn(f64) -> f64) -> f64 {
+    for _ in 0..BISECTION_STEPS {
+        let middle = 0.5 * (above + below);
+        if clearance(middle) > 0.0 {
+            above = middle;
+        } else {
+            below = middle;
+        }
+    }
+    above
+}
+
+/// Halvings of a step when locating the impact, enough to reach double
+/// precision from any reasonable time step.
+const BISECTION_STEPS: usize = 48;
+
+const MAX_FLIGHT_TIME: f64 = 3600.0;
+
+/// Solid rocket motor with constant thrust and mass flow, the usual
+/// textbook model for unguided artillery rockets.
+#[derive(Debug, Clone, Copy)]
+pub struct RocketMotor {
+    /// Seconds.
+    pub burn_time: f64,
+    /// Kilograms, part of the launch mass.
+    pub propellant_mass: f64,
+    /// Newtons, along the direction of flight.
+    pub thrust: f64,
+}
+
+impl RocketMotor {
+    /// Kilograms of propellant burnt per second.
+    pub fn mass_flow(&self) -> f64 {
+        self.propellant_mass / self.burn_time
+    }
+
+    /// `F·t / (ṁ·t·g0)`, the specific impulse in seconds.
+    pub fn specific_impulse(&self) -> f64 {
+        self.thrust / (self.mass_flow() * STANDARD_GRAVITY)
+    }
+}
+
+/// Standard gravity for specific impulse, m/s².
+const STANDARD_GRAVITY: f64 = 9.80665;
+
+/// Point-mass aerodynamic drag: `F = ½·ρ·v²·Cd(M)·A`, with an optional
+/// boost phase.
+#[derive(Debug, Clone)]
+pub struct DragModel {
+    /// Launch mass, including the propellant of `motor`.
+    pub mass: f64,
+    pub reference_area: f64,
+    /// `(Mach, Cd)` pairs sorted by Mach, linearly interpolated.
+    pub drag_table: Vec<(f64, f64)>,
+    /// Motor burning from launch. Without one the projectile leaves the
+    /// launcher at full speed, like a shell.
+    pub motor: Option<RocketMotor>,
+}
+
+impl DragModel {
+    /// Reference area from the calibre in millimetres.
+    pub fn new(mass: f64, diameter_mm: f64, drag_table: Vec<(f64, f64)>, motor: Option<RocketMotor>) -> Self {
+        let radius = diameter_mm / 2000.0;
+        DragModel {
+            mass,
+            reference_area: std::f64::consts::PI * radius * radius,
+            drag_table,
+            motor,
+        }
+    }
+
+    /// Mass `t` seconds after launch, dropping while the motor burns.
+    pub fn mass_at(&self, t: f64) -> f64 {
+        self.mass - self.motor.map_or(0.0, |motor| motor.mass_flow() * t.clamp(0.0, motor.burn_time))
+    }
+
+    /// Thrust `t` seconds after launch.
+    pub fn thrust_at(&self, t: f64) -> f64 {
+        match self.motor {
+            Some(motor) if t < motor.burn_time => motor.thrust,
+            _ => 0.0,
+        }
+    }
+
+    pub fn drag_coefficient(&self, mach: f64) -> f64 {
+        let table = &self.drag_table;
+        match table.iter().position(|&(m, _)| m >= mach) {
+            None => table.last().map_or(0.0, |&(_, cd)| cd),
+            Some(0) => table[0].1,
+            Some(i) => {
+                let (m0, cd0) = table[i - 1];
+                let (m1, cd1) = table[i];
+                cd0 + (cd1 - cd0) * (mach - m0) / (m1 - m0)
+            }
+        }
+    }
+
+    /// Drag force at speed `v` in air of the given density and speed of
+    /// sound.
+    pub fn force(&self, v: f64, density: f64, speed_of_sound: f64) -> f64 {
+        let cd = self.drag_coefficient(v / speed_of_sound);
+        0.5 * density * v * v * cd * self.reference_area
+    }
+}
+
+/// Heights above sea level in meters of the launch site and of the ground
+/// at the target, where a trajectory starts and ends.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Altitudes {
+    pub launch: f64,
+    pub impact: f64,
+}
+
+/// The ground a trajectory flies over.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Ground<'a> {
+    pub altitudes: Altitudes,
+    /// Terrain along the path; without it the ground is level at the
+    /// impact altitude.
+    pub terrain: Option<&'a TerrainProfile>,
+}
+
+impl Ground<'_> {
+    /// Height above sea level at `distance` meters downrange.
+    pub fn height(&self, distance: f64) -> f64 {
+        self.terrain
+            .map_or(self.altitudes.impact, |terrain| terrain.elevation_at(distance))
+    }
+
+    fn lowest(&self) -> f64 {
+        self.terrain.map_or(self.altitudes.impact, |terrain| terrain.lowest().1)
+    }
+}
+
+/// Result of a numerically integrated trajectory over the ground, terrain
+/// included, through the boost phase for a rocket.
+#[derive(Debug, Clone)]
+pub struct DragTrajectory {
+    /// `(t, x, y)` at every integration step, with `y` the altitude above
+    /// sea level, ending at the impact altitude.
+    pub points: Vec<(f64, f64, f64)>,
+    pub range: f64,
+    pub flight_time: f64,
+    /// Apex above the launch site.
+    pub max_height: f64,
+    pub impact_speed: f64,
+    /// See [`GroundImpact::unblocked_range`].
+    pub unblocked_range: Option<f64>,
+    /// End of the motor burn, for a model with a motor.
+    pub burnout: Option<Burnout>,
+}
+
+/// The state where the boost phase hands over to ballistic flight.
+#[derive(Debug, Clone, Copy)]
+pub struct Burnout {
+    pub time: f64,
+    /// Downrange distance from the launch site.
+    pub range: f64,
+    /// Altitude above sea level.
+    pub altitude: f64,
+    pub speed: f64,
+}
+
+impl DragTrajectory {
+    /// Where the trajectory meets the ground.
+    pub fn impact(&self) -> GroundImpact {
+        let (time, range, elevation) = self.points[self.points.len() - 1];
+        GroundImpact {
+            time,
+            range,
+            elevation,
+            unblocked_range: self.unblocked_range,
+        }
+    }
+
+    /// `n` points evenly spaced in time from launch to impact.
+    pub fn sample(&self, n: usize) -> Vec<(f64, f64)> {
+        let last = self.points.len() - 1;
+        (0..n)
+            .map(|i| {
+                let (_, x, y) = self.points[i * last / (n - 1).max(1)];
+                (x, y)
+            })
+            .collect()
+    }
+}
+
+/// Classical fourth-order Runge-Kutta step at time `t` for a 4-component
+/// state.
+pub fn rk4_step(t: f64, state: [f64; 4], dt: f64, f: impl Fn(f64, &[f64; 4]) -> [f64; 4]) -> [f64; 4] {
+    let add = |s: &[f64; 4], k: &[f64; 4], h: f64| {
+        [s[0] + k[0] * h, s[1] + k[1] * h, s[2] + k[2] * h, s[3] + k[3] * h]
+    };
+    let k1 = f(t, &state);
+    let k2 = f(t + dt / 2.0, &add(&state, &k1, dt / 2.0));
+    let k3 = f(t + dt / 2.0, &add(&state, &k2, dt / 2.0));
+    let k4 = f(t + dt, &add(&state, &k3, dt));
+    let mut next = state;
+    for i in 0..4 {
+        next[i] += dt / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]);
+    }
+    next
+}
+
+/// Integrates a point mass with drag from the launch altitude until it
+/// meets the ground, with a fixed RK4 step `dt`. Air density and speed of
+/// sound follow `atmosphere` with altitude. A model with a motor starts from
+/// rest and is pushed along its velocity until burnout, so `v0` is only
+/// used without one.
+pub fn integrate_with_drag(
+    v0: f64,
+    launch_angle_deg: f64,
+    g: f64,
+    model: &DragModel,
+    atmosphere: &Atmosphere,
+    ground: &Ground,
+    dt: f64,
+) -> DragTrajectory {
+    let theta = launch_angle_deg.to_radians();
+    let derivative = |t: f64, s: &[f64; 4], step_start: f64| {
+        let (vx, vy) = (s[2], s[3]);
+        let drag = drag_per_speed(model, atmosphere, s[1], t, vx, vy);
+        let (thrust_x, thrust_y) = thrust_acceleration(model, t, step_start, theta, vx, vy);
+        [vx, vy, thrust_x - drag * vx, thrust_y - g - drag * vy]
+    };
+
+    let v0 = if model.motor.is_some() { 0.0 } else { v0 };
+    let state = [0.0, ground.altitudes.launch, v0 * theta.cos(), v0 * theta.sin()];
+    integrate(state, dt, ground, model, derivative, |s| (s[0], s[1]))
+}
+
+/// Same as [`integrate_with_drag`] but in an Earth-centred frame: gravity
+/// points to the centre and falls off as `g·(R/r)²`, and the range is the
+/// ground arc `R·φ` between launch and impact on a sphere of radius `R`.
+#[allow(clippy::too_many_arguments)]
+pub fn integrate_with_drag_spherical(
+    v0: f64,
+    launch_angle_deg: f64,
+    g: f64,
+    model: &DragModel,
+    atmosphere: &Atmosphere,
+    earth_radius: f64,
+    ground: &Ground,
+    dt: f64,
+) -> DragTrajectory {
+    let theta = launch_angle_deg.to_radians();
+    let gm = g * earth_radius * earth_radius;
+    let derivative = |t: f64, s: &[f64; 4], step_start: f64| {
+        let (x, y, vx, vy) = (s[0], s[1], s[2], s[3]);
+        let r = (x * x + y * y).sqrt();
+        let gravity = gm / (r * r * r);
+        let drag = drag_per_speed(model, atmosphere, r - earth_radius, t, vx, vy);
+        let (thrust_x, thrust_y) = thrust_acceleration(model, t, step_start, theta, vx, vy);
+        [vx, vy, thrust_x - gravity * x - drag * vx, thrust_y - gravity * y - drag * vy]
+    };
+
+    // launch site on the +y axis, local horizontal along +x
+    let v0 = if model.motor.is_some() { 0.0 } else { v0 };
+    let state = [0.0, earth_radius + ground.altitudes.launch, v0 * theta.cos(), v0 * theta.sin()];
+    integrate(state, dt, ground, model, derivative, |s| {
+        let r = (s[0] * s[0] + s[1] * s[1]).sqrt();
+        (earth_radius * s[0].atan2(s[1]), r - earth_radius)
+    })
+}
+
+/// Drag deceleration divided by speed, so that `-k·v` gives the components.
+fn drag_per_speed(model: &DragModel, atmosphere: &Atmosphere, altitude: f64, t: f64, vx: f64, vy: f64) -> f64 {
+    let v = (vx * vx + vy * vy).sqrt();
+    if v > 0.0 {
+        let air = atmosphere.at(altitude);
+        model.force(v, air.density, air.speed_of_sound) / model.mass_at(t) / v
+    } else {
+        0.0
+    }
+}
+
+/// Thrust acceleration components along the velocity, or along the launch
+/// angle `theta` while still at rest on the launcher. Whether the motor
+/// burns is decided at `step_start`, since steps end at burnout and the
+/// last RK4 stage of the final burning step falls on it.
+fn thrust_acceleration(model: &DragModel, t: f64, step_start: f64, theta: f64, vx: f64, vy: f64) -> (f64, f64) {
+    let thrust = model.thrust_at(step_start);
+    if thrust == 0.0 {
+        return (0.0, 0.0);
+    }
+    let acceleration = thrust / model.mass_at(t);
+    let v = (vx * vx + vy * vy).sqrt();
+    if v > 0.0 {
+        (acceleration * vx / v, acceleration * vy / v)
+    } else {
+        (acceleration * theta.cos(), acceleration * theta.sin())
+    }
+}
+
+/// Steps `state` until it meets `ground`, locating the impact within the
+/// last step by bisection. A step ends exactly at burnout when `model` has
+/// a motor. `derivative` takes the time, the state and the start of the
+/// step. `position` maps a state to `(downrange distance, altitude)`. A
+/// target higher than the apex ends the flight at the apex.
+fn integrate(
+    mut state: [f64; 4],
+    dt: f64,
+    ground: &Ground,
+    model: &DragModel,
+    derivative: impl Fn(f64, &[f64; 4], f64) -> [f64; 4],
+    position: impl Fn(&[f64; 4]) -> (f64, f64),
+) -> DragTrajectory {
+    let rk4 = |t: f64, state: [f64; 4], h: f64| rk4_step(t, state, h, |u, s| derivative(u, s, t));
+    let speed = |s: &[f64; 4]| (s[2] * s[2] + s[3] * s[3]).sqrt();
+    let clearance = |s: &[f64; 4]| {
+        let (x, altitude) = position(s);
+        altitude - ground.height(x)
+    };
+    let launch_altitude = ground.altitudes.launch;
+
+    let mut t = 0.0;
+    let (x0, y0) = position(&state);
+    let mut points = vec![(t, x0, y0)];
+    let mut max_height: f64 = y0;
+    let mut burnout = None;
+
+    while t < MAX_FLIGHT_TIME {
+        let burn_end = model
+            .motor
+            .map(|motor| motor.burn_time)
+            .filter(|&burn_time| t < burn_time && t + dt >= burn_time);
+        let step = burn_end.map_or(dt, |burn_time| burn_time - t);
+        let next = rk4(t, state, step);
+        let descending = position(&next).1 < position(&state).1;
+        if hits_ground(clearance(&state), clearance(&next), descending) {
+            let h = if clearance(&state) > 0.0 {
+                bisect(0.0, step, |h| clearance(&rk4(t, state, h)))
+            } else {
+                0.0
+            };
+            let impact = rk4(t, state, h);
+            let (range, impact_altitude) = position(&impact);
+            points.push((t + h, range, impact_altitude));
+            return DragTrajectory {
+                points,
+                range,
+                flight_time: t + h,
+                max_height: max_height - launch_altitude,
+                impact_speed: speed(&impact),
+                unblocked_range: unblocked_range(t + step, next, dt, ground, rk4, &position),
+                burnout,
+            };
+        }
+        state = next;
+        t = burn_end.unwrap_or(t + step);
+        let (x, y) = position(&state);
+        if burn_end.is_some() {
+            burnout = Some(Burnout {
+                time: t,
+                range: x,
+                altitude: y,
+                speed: speed(&state),
+            });
+        }
+        max_height = max_height.max(y);
+        points.push((t, x, y));
+    }
+
+    DragTrajectory {
+        range: position(&state).0,
+        flight_time: t,
+        max_height: max_height - launch_altitude,
+        impact_speed: speed(&state),
+        unblocked_range: None,
+        burnout,
+        points,
+    }
+}
+
+/// Follows the path on from `state` at time `t`, just past where it met the
+/// ground, while it could still clear the ground ahead, and returns where
+/// it comes down again if it does.
+fn unblocked_range(
+    mut t: f64,
+    mut state: [f64; 4],
+    dt: f64,
+    ground: &Ground,
+    rk4: impl Fn(f64, [f64; 4], f64) -> [f64; 4],
+    position: impl Fn(&[f64; 4]) -> (f64, f64),
+) -> Option<f64> {
+    let clearance = |s: &[f64; 4]| {
+        let (x, altitude) = position(s);
+        altitude - ground.height(x)
+    };
+    let lowest = ground.lowest();
+    let mut emerged = false;
+    while position(&state).1 >= lowest && t < MAX_FLIGHT_TIME {
+        let next = rk4(t, state, dt);
+        emerged |= clearance(&state) > 0.0;
+        if emerged && clearance(&next) <= 0.0 {
+            let h = bisect(0.0, dt, |h| clearance(&rk4(t, state, h)));
+            return Some(position(&rk4(t, state, h)).0);
+        }
+        state = next;
+        t += dt;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const G: f64 = 9.81;
+
+    fn level_ground() -> Ground<'static> {
+        Ground::default()
+    }
+
+    fn no_drag() -> DragModel {
+        DragModel::new(66.6, 122.0, vec![(0.0, 0.0)], None)
+    }
+
+    #[test]
+    fn rk4_follows_exponential_decay() {
+        let mut state = [1.0; 4];
+        let dt = 0.01;
+        for i in 0..100 {
+            state = rk4_step(i as f64 * dt, state, dt, |_, s| s.map(|v| -v));
+        }
+        for value in state {
+            assert!((value - (-1f64).exp()).abs() < 1e-10, "{}", value);
+        }
+    }
+
+    #[test]
+    fn drag_coefficient_interpolates_and_clamps() {
+        let model = DragModel::new(66.6, 122.0, vec![(0.5, 0.3), (1.0, 0.5), (2.0, 0.4)], None);
+        assert_eq!(model.drag_coefficient(0.2), 0.3);
+        assert!((model.drag_coefficient(0.75) - 0.4).abs() < 1e-12);
+        assert!((model.drag_coefficient(1.5) - 0.45).abs() < 1e-12);
+        assert_eq!(model.drag_coefficient(3.0), 0.4);
+    }
+
+    #[test]
+    fn drag_force_is_half_rho_v_squared_cd_area() {
+        let model = DragModel::new(66.6, 122.0, vec![(0.0, 0.3)], None);
+        let area = std::f64::consts::PI * 0.061 * 0.061;
+        assert!((model.force(300.0, 1.225, 340.0) - 0.5 * 1.225 * 300.0 * 300.0 * 0.3 * area).abs() < 1e-9);
+    }
+
+    #[test]
+    fn zero_drag_matches_the_vacuum_solution() {
+        let vacuum = VacuumTrajectory::new(690.0, 45.0, G, Altitudes::default());
+        let integrated = integrate_with_drag(
+            690.0,
+            45.0,
+            G,
+            &no_drag(),
+            &Atmosphere::default(),
+            &level_ground(),
+            0.01,
+        );
+        assert!((integrated.range - vacuum.level_range()).abs() < 1e-3, "{}", integrated.range);
+        assert!((integrated.flight_time - vacuum.flight_time()).abs() < 1e-6);
+        assert!((integrated.max_height - vacuum.max_height()).abs() < 1e-2);
+        assert!((integrated.impact_speed - 690.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn drag_shortens_the_range() {
+        let model = DragModel::new(66.6, 122.0, vec![(0.0, 0.3)], None);
+        let vacuum = VacuumTrajectory::new(690.0, 45.0, G, Altitudes::default());
+        let drag = integrate_with_drag(690.0, 45.0, G, &model, &Atmosphere::default(), &level_ground(), 0.01);
+        assert!(drag.range < vacuum.level_range());
+        assert!(drag.impact_speed < 690.0);
+    }
+
+    #[test]
+    fn level_ground_impact_matches_the_closed_form() {
+        let vacuum = VacuumTrajectory::new(300.0, 40.0, G, Altitudes::default());
+        let impact = vacuum.ground_impact(&level_ground(), 0.1);
+        let theta = 40f64.to_radians();
+        assert!((impact.range - 300.0 * 300.0 * (2.0 * theta).sin() / G).abs() < 1e-6, "{}", impact.range);
+        assert!((impact.time - 2.0 * 300.0 * theta.sin() / G).abs() < 1e-9);
+        assert!((0.0..1e-6).contains(&impact.elevation), "{}", impact.elevation);
+        assert!(!impact.blocked());
+    }
+
+    #[test]
+    fn impact_on_a_slope_is_bisected_onto_the_ground() {
+        let slope = TerrainProfile {
+            points: vec![(0.0, 0.0), (20000.0, 1000.0)],
+        };
+        let ground = Ground {
+            altitudes: Altitudes::default(),
+            terrain: Some(&slope),
+        };
+        let vacuum = VacuumTrajectory::new(300.0, 45.0, G, Altitudes::default());
+        let coarse = vacuum.ground_impact(&ground, 1.0);
+        let fine = vacuum.ground_impact(&ground, 0.01);
+        assert!((coarse.elevation - slope.elevation_at(coarse.range)).abs() < 1e-6);
+        assert!((coarse.range - fine.range).abs() < 1e-6);
+        assert!(coarse.range < vacuum.level_range());
+    }
+
+    #[test]
+    fn a_ridge_blocks_the_path_short_of_its_range() {
+        let ridge = TerrainProfile {
+            points: vec![(0.0, 0.0), (7900.0, 0.0), (8000.0, 2000.0), (8400.0, 2000.0), (8500.0, 0.0), (12000.0, 0.0)],
+        };
+        let ground = Ground {
+            altitudes: Altitudes::default(),
+            terrain: Some(&ridge),
+        };
+        let vacuum = VacuumTrajectory::new(300.0, 45.0, G, Altitudes::default());
+        let impact = vacuum.ground_impact(&ground, 0.1);
+        assert!((7900.0..8000.0).contains(&impact.range), "{}", impact.range);
+        assert!(impact.blocked());
+        let unblocked = impact.unblocked_range.unwrap();
+        assert!((unblocked - vacuum.level_range()).abs() < 1e-6, "{}", unblocked);
+    }
+
+    #[test]
+    fn a_target_above_the_apex_ends_the_flight_at_the_apex() {
+        let altitudes = Altitudes {
+            launch: 0.0,
+            impact: 5000.0,
+        };
+        let vacuum = VacuumTrajectory::new(300.0, 45.0, G, altitudes);
+        assert!(!vacuum.reaches_impact_altitude());
+        let impact = vacuum.ground_impact(&Ground { altitudes, terrain: None }, 0.01);
+        let apex_time = 300.0 * 45f64.to_radians().sin() / G;
+        assert!((impact.time - apex_time).abs() <= 0.01, "{}", impact.time);
+        assert!((vacuum.flight_time() - apex_time).abs() < 1e-9);
+    }
+
+    #[test]
+    fn vacuum_range_comes_down_to_the_impact_altitude() {
+        for (launch, impact) in [(500.0, 0.0), (0.0, 300.0), (200.0, 200.0)] {
+            let altitudes = Altitudes { launch, impact };
+            let vacuum = VacuumTrajectory::new(300.0, 40.0, G, altitudes);
+            let (x, y) = vacuum.position(vacuum.flight_time());
+            assert!((y - (impact - launch)).abs() < 1e-9, "{} -> {}: {}", launch, impact, y);
+            assert!((x - vacuum.range()).abs() < 1e-9);
+        }
+        let level = VacuumTrajectory::new(300.0, 40.0, G, Altitudes { launch: 200.0, impact: 200.0 });
+        assert!((level.range() - level.level_range()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn optimal_angle_gives_the_longest_range_between_altitudes() {
+        let level = VacuumTrajectory::optimal(300.0, G, Altitudes::default());
+        assert!((level.theta.to_degrees() - 45.0).abs() < 1e-12);
+        assert!((level.range() - 300.0 * 300.0 / G).abs() < 1e-9);
+
+        for drop in [500.0f64, -300.0] {
+            let altitudes = Altitudes {
+                launch: drop.max(0.0),
+                impact: (-drop).max(0.0),
+            };
+            let optimal = VacuumTrajectory::optimal(300.0, G, altitudes);
+            let expected = 300.0 / G * (300.0 * 300.0 + 2.0 * G * drop).sqrt();
+            assert!((optimal.range() - expected).abs() < 1e-6, "{}: {}", drop, optimal.range());
+            for delta in [-1.0, 1.0] {
+                let other = VacuumTrajectory::new(300.0, optimal.theta.to_degrees() + delta, G, altitudes);
+                assert!(other.range() < optimal.range());
+            }
+        }
+    }
+
+    #[test]
+    fn zero_thrust_motor_matches_the_unpowered_case_from_rest() {
+        let motor = RocketMotor {
+            burn_time: 2.0,
+            propellant_mass: 20.0,
+            thrust: 0.0,
+        };
+        let atmosphere = Atmosphere::default();
+        let ground = Ground {
+            altitudes: Altitudes { launch: 100.0, impact: 0.0 },
+            terrain: None,
+        };
+        let model = DragModel::new(66.6, 122.0, vec![(0.0, 0.0)], Some(motor));
+        let powered = integrate_with_drag(690.0, 45.0, G, &model, &atmosphere, &ground, 0.01);
+        let coasting = integrate_with_drag(0.0, 45.0, G, &no_drag(), &atmosphere, &ground, 0.01);
+        assert!((powered.range - coasting.range).abs() < 1e-9);
+        assert!((powered.flight_time - coasting.flight_time).abs() < 1e-9);
+        assert!((powered.impact_speed - coasting.impact_speed).abs() < 1e-9);
+        assert_eq!(powered.burnout.unwrap().time, 2.0);
+    }
+
+    #[test]
+    fn boost_reaches_the_rocket_equation_speed_and_then_coasts() {
+        let motor = RocketMotor {
+            burn_time: 2.0,
+            propellant_mass: 20.0,
+            thrust: 20000.0,
+        };
+        let model = DragModel::new(66.0, 122.0, vec![(0.0, 0.0)], Some(motor));
+        let flight = integrate_with_drag(0.0, 30.0, 0.0, &model, &Atmosphere::default(), &level_ground(), 0.1);
+
+        // Tsiolkovsky: Δv = Isp·g0·ln(m0 / m1).
+        let burnout = flight.burnout.unwrap();
+        let delta_v = motor.specific_impulse() * STANDARD_GRAVITY * (66.0f64 / 46.0).ln();
+        assert_eq!(burnout.time, 2.0);
+        assert!((burnout.speed - delta_v).abs() < 1e-6, "{} != {}", burnout.speed, delta_v);
+
+        // Without gravity or drag the rocket then flies on in a straight
+        // line at the burnout speed.
+        let (t, x, _) = flight.points[flight.points.len() - 1];
+        let coasted = burnout.range + burnout.speed * 30f64.to_radians().cos() * (t - burnout.time);
+        assert!((x - coasted).abs() < 1e-6 * x, "{} != {}", x, coasted);
+        assert!((flight.impact_speed - burnout.speed).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,302 @@
+use opencv::{core, imgcodecs, prelude::*};
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::fs;
+use std::ops::Range;
+use std::path::PathBuf;
+
+use crate::encode;
+use crate::geo::WGS84_A;
+use crate::map::{MapProjection, Projection, WEB_MERCATOR_MAX_LAT, web_mercator};
+
+/// `[map.basemap]`: raster tiles in the Web Mercator tiling scheme, read
+/// from disk only.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BasemapOptions {
+    /// An MBTiles file, or a directory of `{z}/{x}/{y}.png` tiles.
+    pub path: PathBuf,
+    /// Credit printed on the map. MBTiles files usually carry their own in
+    /// the metadata table; tile directories need it here.
+    pub attribution: Option<String>,
+    /// Zoom level to use instead of the one matching the map scale,
+    /// limited to the levels in the tileset.
+    pub zoom: Option<u8>,
+    /// How far the tiles are faded towards white, 0 to 1, so the range
+    /// rings stay readable over busy maps.
+    #[serde(default = "default_fade")]
+    pub fade: f64,
+}
+
+fn default_fade() -> f64 {
+    0.35
+}
+
+/// Extensions tried, in order, for tiles in a directory.
+const TILE_EXTENSIONS: [&str; 4] = ["png", "jpg", "jpeg", "webp"];
+
+/// Pixels per tile side assumed when picking a zoom level.
+const TILE_SIZE: f64 = 256.0;
+
+/// Deepest zoom level read; tiles are a few centimeters across there, and
+/// deeper levels would overflow the tile indices.
+const MAX_ZOOM: u8 = 24;
+
+/// Shown where a tile is missing from the tileset.
+const MISSING_TILE: [u8; 3] = [235, 235, 235];
+
+enum TileSource {
+    MBTiles(Connection),
+    Directory(PathBuf),
+}
+
+/// A decoded square tile as packed RGB.
+struct Tile {
+    size: usize,
+    rgb: Vec<u8>,
+}
+
+pub struct Basemap {
+    source: TileSource,
+    pub attribution: String,
+    min_zoom: u8,
+    max_zoom: u8,
+    zoom: Option<u8>,
+    fade: f64,
+    tiles: HashMap<(u8, u32, u32), Option<Tile>>,
+}
+
+/// Drops HTML tags and the common entities, since MBTiles attributions
+/// are usually links.
+fn plain_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&copy;", "©")
+        .replace("&amp;", "&")
+        .replace("&nbsp;", " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl Basemap {
+    pub fn open(options: &BasemapOptions) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = &options.path;
+        if !(0.0..=1.0).contains(&options.fade) {
+            return Err(format!("basemap fade {} must be between 0 and 1", options.fade).into());
+        }
+        let (source, metadata_attribution, zooms) = if path.is_dir() {
+            let zooms: Vec<u8> = fs::read_dir(path)
+                .map_err(|e| format!("cannot read basemap {}: {}", path.display(), e))?
+                .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+                .filter(|zoom| *zoom <= MAX_ZOOM)
+                .collect();
+            let zooms = zooms.iter().min().copied().zip(zooms.iter().max().copied());
+            (TileSource::Directory(path.clone()), None, zooms)
+        } else {
+            let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+                .map_err(|e| format!("cannot open basemap {}: {}", path.display(), e))?;
+            let attribution: Option<String> = connection
+                .query_row("SELECT value FROM metadata WHERE name = 'attribution'", [], |row| row.get(0))
+                .optional()
+                .map_err(|e| format!("basemap {} is not an MBTiles file: {}", path.display(), e))?;
+            let zooms: (Option<u8>, Option<u8>) = connection
+                .query_row("SELECT MIN(zoom_level), MAX(zoom_level) FROM tiles", [], |row| {
+                    Ok((row.get(0)?, row.get(1)?))
+                })
+                .map_err(|e| format!("basemap {} is not an MBTiles file: {}", path.display(), e))?;
+            (TileSource::MBTiles(connection), attribution, zooms.0.zip(zooms.1))
+        };
+        let (min_zoom, max_zoom) = zooms
+            .filter(|(min, _)| *min <= MAX_ZOOM)
+            .map(|(min, max)| (min, max.min(MAX_ZOOM)))
+            .ok_or_else(|| format!("basemap {} contains no tiles at zoom 0 to {}", path.display(), MAX_ZOOM))?;
+        let attribution = options
+            .attribution
+            .as_deref()
+            .or(metadata_attribution.as_deref())
+            .map(plain_text)
+            .filter(|text| !text.is_empty())
+            .ok_or_else(|| {
+                format!(
+                    "basemap {} has no attribution, set attribution in [map.basemap]",
+                    path.display()
+                )
+            })?;
+        Ok(Basemap {
+            source,
+            attribution,
+            min_zoom,
+            max_zoom,
+            zoom: options.zoom,
+            fade: options.fade,
+            tiles: HashMap::new(),
+        })
+    }
+
+    /// Zoom level whose tiles have at least the resolution of a map drawn
+    /// in `projection`, limited to the levels in the tileset.
+    fn zoom_for(&self, meters_per_pixel: f64, projection: &MapProjection) -> u8 {
+        let zoom = self.zoom.unwrap_or_else(|| {
+            // Web Mercator maps share the tiles' units; other projections
+            // are in ground meters, which a tile pixel covers fewer of by
+            // cos(lat) away from the equator.
+            let world = match projection.projection {
+                Projection::WebMercator => 2.0 * PI * WGS84_A,
+                Projection::AzimuthalEquidistant => 2.0 * PI * WGS84_A * projection.lat0.to_radians().cos(),
+            };
+            (world / (TILE_SIZE * meters_per_pixel)).log2().ceil().clamp(0.0, MAX_ZOOM as f64) as u8
+        });
+        zoom.clamp(self.min_zoom, self.max_zoom)
+    }
+
+    fn tile_bytes(&self, z: u8, x: u32, y: u32) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+        match &self.source {
+            // MBTiles rows count from the south (TMS).
+            TileSource::MBTiles(connection) => Ok(connection
+                .query_row(
+                    "SELECT tile_data FROM tiles WHERE zoom_level = ?1 AND tile_column = ?2 AND tile_row = ?3",
+                    (z, x, (1u32 << z) - 1 - y),
+                    |row| row.get(0),
+                )
+                .optional()?),
+            TileSource::Directory(dir) => {
+                for extension in TILE_EXTENSIONS {
+                    let path = dir.join(format!("{}/{}/{}.{}", z, x, y, extension));
+                    if path.exists() {
+                        return Ok(Some(
+                            fs::read(&path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?,
+                        ));
+                    }
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    fn tile(&mut self, z: u8, x: u32, y: u32) -> Result<Option<&Tile>, Box<dyn std::error::Error>> {
+        if !self.tiles.contains_key(&(z, x, y)) {
+            let tile = match self.tile_bytes(z, x, y)? {
+                Some(bytes) => {
+                    let image = imgcodecs::imdecode(&core::Vector::<u8>::from_slice(&bytes), imgcodecs::IMREAD_COLOR)?;
+                    if image.empty() || image.rows() != image.cols() {
+                        return Err(format!("cannot decode basemap tile {}/{}/{}", z, x, y).into());
+                    }
+                    Some(Tile {
+                        size: image.cols() as usize,
+                        rgb: encode::mat_to_rgb(&image)?,
+                    })
+                }
+                None => None,
+            };
+            self.tiles.insert((z, x, y), tile);
+        }
+        Ok(self.tiles[&(z, x, y)].as_ref())
+    }
+
+    /// A `width` x `height` RGB image of the area `x` by `y`, in meters of
+    /// `projection`, resampled from the tiles at the nearest pixel.
+    pub fn render(
+        &mut self,
+        projection: &MapProjection,
+        x: Range<f64>,
+        y: Range<f64>,
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let meters_per_pixel = (x.end - x.start) / width as f64;
+        let zoom = self.zoom_for(meters_per_pixel, projection);
+        let tiles = (1u32 << zoom) as f64;
+        let fade = self.fade;
+        let mut image = Vec::with_capacity((width * height * 3) as usize);
+        for row in 0..height {
+            let northing = y.end - (row as f64 + 0.5) / height as f64 * (y.end - y.start);
+            for column in 0..width {
+                let easting = x.start + (column as f64 + 0.5) / width as f64 * (x.end - x.start);
+                let (lat, lon) = projection.unproject(easting, northing);
+                let mut pixel = MISSING_TILE;
+                if lat.abs() <= WEB_MERCATOR_MAX_LAT {
+                    // Position in tiles from the top left corner of the world.
+                    let (mx, my) = web_mercator(lat, lon);
+                    let tx = (mx / (2.0 * PI * WGS84_A) + 0.5) * tiles;
+                    let ty = (0.5 - my / (2.0 * PI * WGS84_A)) * tiles;
+                    let (x_tile, y_tile) = (tx.floor().clamp(0.0, tiles - 1.0), ty.floor().clamp(0.0, tiles - 1.0));
+                    if let Some(tile) = self.tile(zoom, x_tile as u32, y_tile as u32)? {
+                        let px = (((tx - x_tile) * tile.size as f64) as usize).min(tile.size - 1);
+                        let py = (((ty - y_tile) * tile.size as f64) as usize).min(tile.size - 1);
+                        let offset = (py * tile.size + px) * 3;
+                        pixel.copy_from_slice(&tile.rgb[offset..offset + 3]);
+                    }
+                }
+                image.extend(pixel.map(|c| (c as f64 * (1.0 - fade) + 255.0 * fade).round() as u8));
+            }
+        }
+        Ok(image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn basemap(zoom: Option<u8>) -> Basemap {
+        Basemap {
+            source: TileSource::Directory(PathBuf::new()),
+            attribution: String::new(),
+            min_zoom: 5,
+            max_zoom: 18,
+            zoom,
+            fade: 0.0,
+            tiles: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn zoom_matches_the_projection_scale() {
+        // Just coarser than the zoom 10 tiles at 60 degrees north, where a
+        // ground meter is two Web Mercator meters.
+        let meters_per_pixel = 2.0 * PI * WGS84_A / (TILE_SIZE * 1024.0) * 1.01;
+        let mercator = MapProjection::new(Projection::WebMercator, 60.0, 0.0);
+        let azimuthal = MapProjection::new(Projection::AzimuthalEquidistant, 60.0, 0.0);
+        assert_eq!(basemap(None).zoom_for(meters_per_pixel, &mercator), 10);
+        assert_eq!(basemap(None).zoom_for(meters_per_pixel, &azimuthal), 9);
+        assert_eq!(basemap(None).zoom_for(1e-6, &mercator), 18);
+        assert_eq!(basemap(None).zoom_for(1e9, &mercator), 5);
+    }
+
+    #[test]
+    fn zoom_override_is_limited_to_the_tileset() {
+        let projection = MapProjection::new(Projection::WebMercator, 0.0, 0.0);
+        assert_eq!(basemap(Some(12)).zoom_for(1.0, &projection), 12);
+        assert_eq!(basemap(Some(30)).zoom_for(1.0, &projection), 18);
+        assert_eq!(basemap(Some(2)).zoom_for(1.0, &projection), 5);
+    }
+
+    #[test]
+    fn directory_skips_zoom_levels_beyond_the_deepest() {
+        let dir = std::env::temp_dir().join(format!("bm21-basemap-{}", std::process::id()));
+        for zoom in ["3", "12", "30", "attribution"] {
+            fs::create_dir_all(dir.join(zoom)).unwrap();
+        }
+        let options = BasemapOptions {
+            path: dir.clone(),
+            attribution: Some("Test tiles".to_string()),
+            zoom: None,
+            fade: default_fade(),
+        };
+        let basemap = Basemap::open(&options);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let basemap = basemap.unwrap();
+        assert_eq!((basemap.min_zoom, basemap.max_zoom), (3, 12));
+    }
+}
@@ -0,0 +1,762 @@
+use std::fmt;
+
+use crate::geo::{WGS84_A, WGS84_F};
+
+const UTM_K0: f64 = 0.9996;
+const UTM_FALSE_EASTING: f64 = 500000.0;
+const UTM_FALSE_NORTHING_SOUTH: f64 = 10000000.0;
+
+const LATITUDE_BANDS: &str = "CDEFGHJKLMNPQRSTUVWX";
+const MGRS_COLUMN_LETTERS: [&str; 3] = ["ABCDEFGH", "JKLMNPQR", "STUVWXYZ"];
+const MGRS_ROW_LETTERS: [&str; 2] = ["ABCDEFGHJKLMNPQRSTUV", "FGHJKLMNPQRSTUVABCDE"];
+
+const PLUS_CODE_ALPHABET: &str = "23456789CFGHJMPQRVWX";
+const PLUS_CODE_SEPARATOR_POSITION: usize = 8;
+const PLUS_CODE_PAIR_RESOLUTIONS: [f64; 5] = [20.0, 1.0, 0.05, 0.0025, 0.000125];
+const PLUS_CODE_GRID_ROWS: f64 = 5.0;
+const PLUS_CODE_GRID_COLUMNS: f64 = 4.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CoordinateError {
+    Empty,
+    Malformed(String),
+    OutOfRange(String),
+}
+
+impl fmt::Display for CoordinateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoordinateError::Empty => write!(f, "empty coordinate"),
+            CoordinateError::Malformed(msg) => write!(f, "malformed coordinate: {}", msg),
+            CoordinateError::OutOfRange(msg) => write!(f, "coordinate out of range: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for CoordinateError {}
+
+/// Parses a position given as decimal degrees, degrees-minutes-seconds, UTM
+/// (`48P 312212 1587795` or `48 north 312212 1587795`), MGRS
+/// (`48PUA 1221 8779`) or a full Plus Code (`7P659745+9C`) and returns WGS84
+/// `(lat, lon)` in decimal degrees.
+pub fn parse_coordinate(input: &str) -> Result<(f64, f64), CoordinateError> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(CoordinateError::Empty);
+    }
+
+    if looks_like_plus_code(input) {
+        return parse_plus_code(input);
+    }
+    if let Some(result) = try_parse_mgrs(input) {
+        return result;
+    }
+    if let Some(result) = try_parse_utm(input) {
+        return result;
+    }
+    parse_degrees(input)
+}
+
+pub fn validate_lat_lon(lat: f64, lon: f64) -> Result<(f64, f64), CoordinateError> {
+    if !lat.is_finite() || !(-90.0..=90.0).contains(&lat) {
+        return Err(CoordinateError::OutOfRange(format!(
+            "latitude {} outside -90..90",
+            lat
+        )));
+    }
+    if !lon.is_finite() || !(-180.0..=180.0).contains(&lon) {
+        return Err(CoordinateError::OutOfRange(format!(
+            "longitude {} outside -180..180",
+            lon
+        )));
+    }
+    Ok((lat, lon))
+}
+
+/// Decimal degrees or degrees-minutes-seconds, with optional N/S/E/W
+/// hemisphere letters before or after each component.
+pub fn parse_degrees(input: &str) -> Result<(f64, f64), CoordinateError> {
+    let mut tokens = Vec::new();
+    let mut number = String::new();
+    for c in input.chars() {
+        if c.is_ascii_digit() || c == '.' || ((c == '-' || c == '+') && number.is_empty()) {
+            number.push(c);
+            continue;
+        }
+        if !number.is_empty() {
+            tokens.push(DegreeToken::Number(std::mem::take(&mut number)));
+        }
+        match c.to_ascii_uppercase() {
+            'N' | 'S' | 'E' | 'W' => tokens.push(DegreeToken::Hemisphere(c.to_ascii_uppercase())),
+            '°' | 'º' | '\'' | '′' | '"' | '″' | ',' | ';' | ':' => {}
+            c if c.is_whitespace() => {}
+            _ => {
+                return Err(CoordinateError::Malformed(format!(
+                    "unexpected character '{}' in \"{}\"",
+                    c, input
+                )));
+            }
+        }
+    }
+    if !number.is_empty() {
+        tokens.push(DegreeToken::Number(number));
+    }
+
+    let groups = group_degree_tokens(&tokens, input)?;
+    let (first, second) = match groups.as_slice() {
+        [first, second] => (first, second),
+        _ => {
+            return Err(CoordinateError::Malformed(format!(
+                "expected a latitude and a longitude in \"{}\"",
+                input
+            )));
+        }
+    };
+
+    let (lat_group, lon_group) = match (first.1, second.1) {
+        (Some('E' | 'W'), Some('N' | 'S')) | (Some('E' | 'W'), None) | (None, Some('N' | 'S')) => {
+            (second, first)
+        }
+        _ => (first, second),
+    };
+    if matches!(lat_group.1, Some('E' | 'W')) || matches!(lon_group.1, Some('N' | 'S')) {
+        return Err(CoordinateError::Malformed(format!(
+            "conflicting hemisphere letters in \"{}\"",
+            input
+        )));
+    }
+
+    let lat = component_to_degrees(&lat_group.0, lat_group.1, input)?;
+    let lon = component_to_degrees(&lon_group.0, lon_group.1, input)?;
+    validate_lat_lon(lat, lon)
+}
+
+enum DegreeToken {
+    Number(String),
+    Hemisphere(char),
+}
+
+type DegreeGroup = (Vec<String>, Option<char>);
+
+fn group_degree_tokens(
+    tokens: &[DegreeToken],
+    input: &str,
+) -> Result<Vec<DegreeGroup>, CoordinateError> {
+    let has_hemispheres = tokens.iter().any(|t| matches!(t, DegreeToken::Hemisphere(_)));
+    if !has_hemispheres {
+        let numbers: Vec<String> = tokens
+            .iter()
+            .filter_map(|t| match t {
+                DegreeToken::Number(n) => Some(n.clone()),
+                DegreeToken::Hemisphere(_) => None,
+            })
+            .collect();
+        if numbers.is_empty() || !numbers.len().is_multiple_of(2) || numbers.len() > 6 {
+            return Err(CoordinateError::Malformed(format!(
+                "cannot split \"{}\" into latitude and longitude",
+                input
+            )));
+        }
+        let half = numbers.len() / 2;
+        return Ok(vec![
+            (numbers[..half].to_vec(), None),
+            (numbers[half..].to_vec(), None),
+        ]);
+    }
+
+    let prefix = matches!(tokens.first(), Some(DegreeToken::Hemisphere(_)));
+    let mut groups: Vec<DegreeGroup> = Vec::new();
+    let mut current: DegreeGroup = (Vec::new(), None);
+    for token in tokens {
+        match token {
+            DegreeToken::Number(n) => current.0.push(n.clone()),
+            DegreeToken::Hemisphere(h) if prefix => {
+                if current.1.is_some() || !current.0.is_empty() {
+                    groups.push(std::mem::take(&mut current));
+                }
+                current.1 = Some(*h);
+            }
+            DegreeToken::Hemisphere(h) => {
+                current.1 = Some(*h);
+                groups.push(std::mem::take(&mut current));
+            }
+        }
+    }
+    if !current.0.is_empty() || current.1.is_some() {
+        groups.push(current);
+    }
+    Ok(groups)
+}
+
+fn component_to_degrees(
+    parts: &[String],
+    hemisphere: Option<char>,
+    input: &str,
+) -> Result<f64, CoordinateError> {
+    if parts.is_empty() || parts.len() > 3 {
+        return Err(CoordinateError::Malformed(format!(
+            "expected degrees, minutes and seconds in \"{}\"",
+            input
+        )));
+    }
+    let values = parts
+        .iter()
+        .map(|p| {
+            p.parse::<f64>()
+                .map_err(|_| CoordinateError::Malformed(format!("invalid number \"{}\"", p)))
+        })
+        .collect::<Result<Vec<f64>, _>>()?;
+
+    for (value, unit) in values.iter().skip(1).zip(["minutes", "seconds"]) {
+        if !(0.0..60.0).contains(value) {
+            return Err(CoordinateError::OutOfRange(format!(
+                "{} {} must be in 0..60",
+                value, unit
+            )));
+        }
+    }
+    if values[..values.len() - 1].iter().any(|v| v.fract() != 0.0) {
+        return Err(CoordinateError::Malformed(format!(
+            "only the last component may have a fraction in \"{}\"",
+            input
+        )));
+    }
+
+    let degrees = values[0];
+    let magnitude = degrees.abs()
+        + values.get(1).copied().unwrap_or(0.0) / 60.0
+        + values.get(2).copied().unwrap_or(0.0) / 3600.0;
+    let negative = degrees.is_sign_negative() || matches!(hemisphere, Some('S' | 'W'));
+    if degrees.is_sign_negative() && hemisphere.is_some() {
+        return Err(CoordinateError::Malformed(format!(
+            "negative degrees combined with a hemisphere letter in \"{}\"",
+            input
+        )));
+    }
+    Ok(if negative { -magnitude } else { magnitude })
+}
+
+/// `zone band easting northing`, e.g. `48P 312212 1587795` or `48 P 312212 1587795`,
+/// or `zone hemisphere easting northing` with the hemisphere spelled out, e.g.
+/// `48 south 500000 8500000`.
+fn try_parse_utm(input: &str) -> Option<Result<(f64, f64), CoordinateError>> {
+    let tokens: Vec<&str> = input.split_whitespace().collect();
+    let (zone_band, easting, northing) = match tokens.as_slice() {
+        [zone_band, e, n] => (zone_band.to_string(), *e, *n),
+        [zone, hemisphere, e, n] if hemisphere_word(hemisphere).is_some() => {
+            let zone = zone.parse::<u32>().ok()?;
+            let easting = e.parse::<f64>().ok()?;
+            let northing = n.parse::<f64>().ok()?;
+            let northern = hemisphere_word(hemisphere)?;
+            return Some(utm_hemisphere_to_lat_lon(zone, northern, easting, northing));
+        }
+        [zone, band, e, n] => (format!("{}{}", zone, band), *e, *n),
+        _ => return None,
+    };
+    let (zone, band) = split_zone_band(&zone_band)?;
+    let easting = easting.parse::<f64>().ok()?;
+    let northing = northing.parse::<f64>().ok()?;
+    // The letter is read as a latitude band. `S` also reads as "southern
+    // hemisphere" in the common `48S` shorthand and the two never agree, so
+    // it is rejected; `N` is only accepted where band N and the northern
+    // hemisphere give the same point.
+    let spelled_out = |hemisphere: &str| {
+        format!("\"{} {} {} {}\"", zone, hemisphere, easting, northing)
+    };
+    if band == 'S' {
+        return Some(Err(CoordinateError::Malformed(format!(
+            "UTM letter 'S' could be latitude band S or the southern hemisphere; write {} or {}",
+            spelled_out("north"),
+            spelled_out("south")
+        ))));
+    }
+    let result = utm_to_lat_lon(zone, band, easting, northing);
+    if band == 'N' && matches!(result, Err(CoordinateError::OutOfRange(_))) {
+        return Some(Err(CoordinateError::OutOfRange(format!(
+            "northing {} falls outside latitude band N; for the northern hemisphere write {}",
+            northing,
+            spelled_out("north")
+        ))));
+    }
+    Some(result)
+}
+
+fn hemisphere_word(word: &str) -> Option<bool> {
+    match word.to_ascii_lowercase().as_str() {
+        "north" => Some(true),
+        "south" => Some(false),
+        _ => None,
+    }
+}
+
+/// UTM with an explicit hemisphere instead of a latitude band.
+pub fn utm_hemisphere_to_lat_lon(
+    zone: u32,
+    northern: bool,
+    easting: f64,
+    northing: f64,
+) -> Result<(f64, f64), CoordinateError> {
+    check_utm(zone, easting, northing)?;
+    let (lat, lon) = utm_inverse(zone, northern, easting, northing);
+    validate_lat_lon(lat, lon)
+}
+
+/// UTM with a latitude band letter; `S` is band S (32-40 N), not the
+/// southern hemisphere. See [`utm_hemisphere_to_lat_lon`] for the
+/// hemisphere form.
+pub fn utm_to_lat_lon(
+    zone: u32,
+    band: char,
+    easting: f64,
+    northing: f64,
+) -> Result<(f64, f64), CoordinateError> {
+    let band = band.to_ascii_uppercase();
+    if !LATITUDE_BANDS.contains(band) {
+        return Err(CoordinateError::Malformed(format!(
+            "invalid latitude band '{}'",
+            band
+        )));
+    }
+    check_utm(zone, easting, northing)?;
+
+    let northern = band >= 'N';
+    let (lat, lon) = utm_inverse(zone, northern, easting, northing);
+    let (band_south, band_north) = band_latitudes(band);
+    if lat < band_south - 0.5 || lat > band_north + 0.5 {
+        return Err(CoordinateError::OutOfRange(format!(
+            "northing {} falls outside latitude band {}",
+            northing, band
+        )));
+    }
+    validate_lat_lon(lat, lon)
+}
+
+fn check_utm(zone: u32, easting: f64, northing: f64) -> Result<(), CoordinateError> {
+    if !(1..=60).contains(&zone) {
+        return Err(CoordinateError::OutOfRange(format!(
+            "UTM zone {} outside 1..60",
+            zone
+        )));
+    }
+    if !(100000.0..=900000.0).contains(&easting) {
+        return Err(CoordinateError::OutOfRange(format!(
+            "UTM easting {} outside 100000..900000",
+            easting
+        )));
+    }
+    if !(0.0..=UTM_FALSE_NORTHING_SOUTH).contains(&northing) {
+        return Err(CoordinateError::OutOfRange(format!(
+            "UTM northing {} outside 0..10000000",
+            northing
+        )));
+    }
+    Ok(())
+}
+
+/// `zone band column row easting northing`, e.g. `48PUA 1221 8779` or
+/// `48PUA12218779`. The centre of the referenced grid square is returned.
+fn try_parse_mgrs(input: &str) -> Option<Result<(f64, f64), CoordinateError>> {
+    let compact: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    let zone_len = compact.chars().take_while(|c| c.is_ascii_digit()).count();
+    if zone_len == 0 || zone_len > 2 {
+        return None;
+    }
+    if !compact[zone_len..].chars().next()?.is_ascii_alphabetic() || compact.len() < zone_len + 3 {
+        return None;
+    }
+    let (zone_band, rest) = compact.split_at(zone_len + 1);
+    let (zone, band) = split_zone_band(zone_band)?;
+    let letters: Vec<char> = rest.chars().take(2).collect();
+    if !letters.iter().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let digits = &rest[2..];
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(mgrs_to_lat_lon(zone, band, letters[0], letters[1], digits))
+}
+
+pub fn mgrs_to_lat_lon(
+    zone: u32,
+    band: char,
+    column: char,
+    row: char,
+    digits: &str,
+) -> Result<(f64, f64), CoordinateError> {
+    if !(1..=60).contains(&zone) {
+        return Err(CoordinateError::OutOfRange(format!(
+            "MGRS zone {} outside 1..60",
+            zone
+        )));
+    }
+    let band = band.to_ascii_uppercase();
+    if !LATITUDE_BANDS.contains(band) {
+        return Err(CoordinateError::Malformed(format!(
+            "invalid latitude band '{}'",
+            band
+        )));
+    }
+    if !digits.len().is_multiple_of(2) || digits.len() > 10 {
+        return Err(CoordinateError::Malformed(format!(
+            "MGRS needs an even number of digits (at most 10), got \"{}\"",
+            digits
+        )));
+    }
+
+    let column = column.to_ascii_uppercase();
+    let row = row.to_ascii_uppercase();
+    let column_index = MGRS_COLUMN_LETTERS[((zone - 1) % 3) as usize]
+        .find(column)
+        .ok_or_else(|| {
+            CoordinateError::Malformed(format!(
+                "column letter '{}' is not used in zone {}",
+                column, zone
+            ))
+        })?;
+    let row_index = MGRS_ROW_LETTERS[((zone - 1) % 2) as usize]
+        .find(row)
+        .ok_or_else(|| CoordinateError::Malformed(format!("invalid row letter '{}'", row)))?;
+
+    let precision = digits.len() / 2;
+    let resolution = 10f64.powi(5 - precision as i32);
+    let (east_digits, north_digits) = digits.split_at(precision);
+    let parse = |s: &str| s.parse::<f64>().unwrap_or(0.0) * resolution;
+
+    let easting = (column_index as f64 + 1.0) * 100000.0 + parse(east_digits) + resolution / 2.0;
+    let mut northing = row_index as f64 * 100000.0 + parse(north_digits) + resolution / 2.0;
+
+    let northern = band >= 'N';
+    let (band_south, _) = band_latitudes(band);
+    let band_northing = (central_meridian_northing(band_south, northern) / 100000.0).floor()
+        * 100000.0;
+    while northing < band_northing {
+        northing += 2000000.0;
+    }
+
+    utm_to_lat_lon(zone, band, easting, northing)
+}
+
+/// True when `input` has the layout of an Open Location Code: a single '+'
+/// at an even position no later than the eighth character, with only code
+/// characters (and '0' padding) around it. Signed decimals such as
+/// `+14.3559,+103.2586` do not match.
+fn looks_like_plus_code(input: &str) -> bool {
+    let code = input.to_ascii_uppercase();
+    match code.find('+') {
+        Some(separator) => {
+            separator <= PLUS_CODE_SEPARATOR_POSITION
+                && separator % 2 == 0
+                && code.matches('+').count() == 1
+                && code.chars().all(|c| c == '+' || c == '0' || PLUS_CODE_ALPHABET.contains(c))
+        }
+        None => false,
+    }
+}
+
+/// Full (global) Open Location Code such as `7P659745+9C`. Short codes need a
+/// reference locality and are rejected.
+pub fn parse_plus_code(input: &str) -> Result<(f64, f64), CoordinateError> {
+    let code = input.trim().to_ascii_uppercase();
+    let separator = code.find('+').ok_or_else(|| {
+        CoordinateError::Malformed(format!("plus code \"{}\" has no '+'", input))
+    })?;
+    if separator != PLUS_CODE_SEPARATOR_POSITION {
+        return Err(CoordinateError::Malformed(format!(
+            "\"{}\" is a short plus code; a full 8-character prefix is required",
+            input
+        )));
+    }
+
+    let digits: String = code.chars().filter(|&c| c != '+' && c != '0').collect();
+    if digits.len() < 2 || digits.len() > 15 {
+        return Err(CoordinateError::Malformed(format!(
+            "invalid plus code length in \"{}\"",
+            input
+        )));
+    }
+    let values = digits
+        .chars()
+        .map(|c| {
+            PLUS_CODE_ALPHABET.find(c).ok_or_else(|| {
+                CoordinateError::Malformed(format!("invalid plus code character '{}'", c))
+            })
+        })
+        .collect::<Result<Vec<usize>, _>>()?;
+    if values[0] > 8 || values[1] > 17 {
+        return Err(CoordinateError::OutOfRange(format!(
+            "plus code \"{}\" encodes a position outside the globe",
+            input
+        )));
+    }
+
+    let mut lat = -90.0;
+    let mut lon = -180.0;
+    let mut lat_size = 0.0;
+    let mut lon_size = 0.0;
+    for (pair, resolution) in values.chunks(2).take(5).zip(PLUS_CODE_PAIR_RESOLUTIONS) {
+        lat += pair[0] as f64 * resolution;
+        lat_size = resolution;
+        if let Some(&lon_value) = pair.get(1) {
+            lon += lon_value as f64 * resolution;
+            lon_size = resolution;
+        }
+    }
+    for &value in values.iter().skip(10) {
+        lat_size /= PLUS_CODE_GRID_ROWS;
+        lon_size /= PLUS_CODE_GRID_COLUMNS;
+        lat += (value / PLUS_CODE_GRID_COLUMNS as usize) as f64 * lat_size;
+        lon += (value % PLUS_CODE_GRID_COLUMNS as usize) as f64 * lon_size;
+    }
+
+    validate_lat_lon(
+        (lat + lat_size / 2.0).min(90.0),
+        (lon + lon_size / 2.0).min(180.0),
+    )
+}
+
+fn split_zone_band(zone_band: &str) -> Option<(u32, char)> {
+    let band = zone_band.chars().last()?.to_ascii_uppercase();
+    let zone = zone_band[..zone_band.len() - band.len_utf8()].parse::<u32>().ok()?;
+    if !LATITUDE_BANDS.contains(band) {
+        return None;
+    }
+    Some((zone, band))
+}
+
+fn band_latitudes(band: char) -> (f64, f64) {
+    let index = LATITUDE_BANDS.find(band).unwrap_or(0) as f64;
+    let south = -80.0 + index * 8.0;
+    // band X spans 12 degrees
+    let north = if band == 'X' { 84.0 } else { south + 8.0 };
+    (south, north)
+}
+
+fn ellipsoid_constants() -> (f64, f64) {
+    let e2 = WGS84_F * (2.0 - WGS84_F);
+    (e2, e2 / (1.0 - e2))
+}
+
+fn meridian_arc(lat_rad: f64) -> f64 {
+    let (e2, _) = ellipsoid_constants();
+    let e4 = e2 * e2;
+    let e6 = e4 * e2;
+    WGS84_A
+        * ((1.0 - e2 / 4.0 - 3.0 * e4 / 64.0 - 5.0 * e6 / 256.0) * lat_rad
+            - (3.0 * e2 / 8.0 + 3.0 * e4 / 32.0 + 45.0 * e6 / 1024.0) * (2.0 * lat_rad).sin()
+            + (15.0 * e4 / 256.0 + 45.0 * e6 / 1024.0) * (4.0 * lat_rad).sin()
+            - (35.0 * e6 / 3072.0) * (6.0 * lat_rad).sin())
+}
+
+fn central_meridian_northing(lat: f64, northern: bool) -> f64 {
+    let northing = UTM_K0 * meridian_arc(lat.to_radians());
+    if northern {
+        northing
+    } else {
+        northing + UTM_FALSE_NORTHING_SOUTH
+    }
+}
+
+/// Transverse Mercator inverse (Snyder, USGS PP 1395) for a UTM zone.
+fn utm_inverse(zone: u32, northern: bool, easting: f64, northing: f64) -> (f64, f64) {
+    let (e2, ep2) = ellipsoid_constants();
+    let x = easting - UTM_FALSE_EASTING;
+    let y = if northern {
+        northing
+    } else {
+        northing - UTM_FALSE_NORTHING_SOUTH
+    };
+    let lon0 = (zone as f64 - 1.0) * 6.0 - 180.0 + 3.0;
+
+    let m = y / UTM_K0;
+    let mu = m / (WGS84_A * (1.0 - e2 / 4.0 - 3.0 * e2 * e2 / 64.0 - 5.0 * e2.powi(3) / 256.0));
+    let e1 = (1.0 - (1.0 - e2).sqrt()) / (1.0 + (1.0 - e2).sqrt());
+    let phi1 = mu
+        + (3.0 * e1 / 2.0 - 27.0 * e1.powi(3) / 32.0) * (2.0 * mu).sin()
+        + (21.0 * e1 * e1 / 16.0 - 55.0 * e1.powi(4) / 32.0) * (4.0 * mu).sin()
+        + (151.0 * e1.powi(3) / 96.0) * (6.0 * mu).sin()
+        + (1097.0 * e1.powi(4) / 512.0) * (8.0 * mu).sin();
+
+    let (sin_phi1, cos_phi1) = phi1.sin_cos();
+    let tan_phi1 = phi1.tan();
+    let c1 = ep2 * cos_phi1 * cos_phi1;
+    let t1 = tan_phi1 * tan_phi1;
+    let n1 = WGS84_A / (1.0 - e2 * sin_phi1 * sin_phi1).sqrt();
+    let r1 = WGS84_A * (1.0 - e2) / (1.0 - e2 * sin_phi1 * sin_phi1).powf(1.5);
+    let d = x / (n1 * UTM_K0);
+
+    let lat = phi1
+        - (n1 * tan_phi1 / r1)
+            * (d * d / 2.0
+                - (5.0 + 3.0 * t1 + 10.0 * c1 - 4.0 * c1 * c1 - 9.0 * ep2) * d.powi(4) / 24.0
+                + (61.0 + 90.0 * t1 + 298.0 * c1 + 45.0 * t1 * t1 - 252.0 * ep2 - 3.0 * c1 * c1)
+                    * d.powi(6)
+                    / 720.0);
+    let lon = (d - (1.0 + 2.0 * t1 + c1) * d.powi(3) / 6.0
+        + (5.0 - 2.0 * c1 + 28.0 * t1 - 3.0 * c1 * c1 + 8.0 * ep2 + 24.0 * t1 * t1) * d.powi(5)
+            / 120.0)
+        / cos_phi1;
+
+    (lat.to_degrees(), lon0 + lon.to_degrees())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Transverse Mercator forward (Snyder, USGS PP 1395), the inverse of
+    /// `utm_inverse`, for round trips.
+    fn utm_forward(lat: f64, lon: f64) -> (u32, char, f64, f64) {
+        let zone = ((lon + 180.0) / 6.0).floor() as u32 % 60 + 1;
+        let band = LATITUDE_BANDS
+            .chars()
+            .nth((((lat + 80.0) / 8.0).floor() as usize).min(19))
+            .unwrap();
+        let lon0 = (zone as f64 - 1.0) * 6.0 - 180.0 + 3.0;
+        let (e2, ep2) = ellipsoid_constants();
+        let phi = lat.to_radians();
+        let (sin_phi, cos_phi) = phi.sin_cos();
+        let n = WGS84_A / (1.0 - e2 * sin_phi * sin_phi).sqrt();
+        let t = phi.tan().powi(2);
+        let c = ep2 * cos_phi * cos_phi;
+        let a = cos_phi * (lon - lon0).to_radians();
+        let easting = UTM_FALSE_EASTING
+            + UTM_K0
+                * n
+                * (a + (1.0 - t + c) * a.powi(3) / 6.0
+                    + (5.0 - 18.0 * t + t * t + 72.0 * c - 58.0 * ep2) * a.powi(5) / 120.0);
+        let northing = central_meridian_northing(lat, lat >= 0.0)
+            + UTM_K0
+                * n
+                * phi.tan()
+                * (a * a / 2.0
+                    + (5.0 - t + 9.0 * c + 4.0 * c * c) * a.powi(4) / 24.0
+                    + (61.0 - 58.0 * t + t * t + 600.0 * c - 330.0 * ep2) * a.powi(6) / 720.0);
+        (zone, band, easting, northing)
+    }
+
+    fn assert_close(actual: (f64, f64), expected: (f64, f64), tolerance: f64) {
+        assert!(
+            (actual.0 - expected.0).abs() < tolerance && (actual.1 - expected.1).abs() < tolerance,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    const SITES: [(f64, f64); 6] = [
+        (14.3559, 103.2586),
+        (13.9, 102.5),
+        (-33.8688, 151.2093),
+        (-22.9068, -43.1729),
+        (51.4779, -0.0015),
+        (-1.2921, 36.8219),
+    ];
+
+    #[test]
+    fn decimal_and_dms() {
+        assert_close(parse_coordinate("14.3559, 103.2586").unwrap(), (14.3559, 103.2586), 1e-12);
+        assert_close(parse_coordinate("-33.9, -70.5").unwrap(), (-33.9, -70.5), 1e-12);
+        assert_close(
+            parse_coordinate("14°21'21.2\"N 103°15'31.0\"E").unwrap(),
+            (14.0 + 21.0 / 60.0 + 21.2 / 3600.0, 103.0 + 15.0 / 60.0 + 31.0 / 3600.0),
+            1e-12,
+        );
+        assert_close(parse_coordinate("33 52 S 151 12 E").unwrap(), (-(33.0 + 52.0 / 60.0), 151.2), 1e-12);
+    }
+
+    #[test]
+    fn signed_decimals_are_not_plus_codes() {
+        assert_close(parse_coordinate("+14.3559,+103.2586").unwrap(), (14.3559, 103.2586), 1e-12);
+        assert_close(parse_coordinate("-14.3559,+103.2586").unwrap(), (-14.3559, 103.2586), 1e-12);
+    }
+
+    #[test]
+    fn documented_examples_decode_to_the_launch_site() {
+        // 7P659745+9C is the 14 m cell around the default launch site.
+        assert_close(parse_coordinate("7P659745+9C").unwrap(), (14.3559, 103.2586), 1e-4);
+        assert_close(parse_coordinate("48P 312212 1587795").unwrap(), (14.3559, 103.2586), 1e-5);
+        assert_close(parse_coordinate("48PUA 1221 8779").unwrap(), (14.3559, 103.2586), 1e-4);
+        assert!(matches!(parse_coordinate("CMCV+2W"), Err(CoordinateError::Malformed(_))));
+    }
+
+    #[test]
+    fn utm_reference_points() {
+        // On the central meridian the easting is the false easting and the
+        // northing k0 times the meridian arc, 4,984,944.378 m to 45 deg.
+        assert_close(parse_coordinate("48N 500000 0").unwrap(), (0.0, 105.0), 1e-9);
+        assert_close(
+            parse_coordinate("48 north 500000 4982950.400").unwrap(),
+            (45.0, 105.0),
+            1e-6,
+        );
+        assert_close(
+            parse_coordinate("48 south 500000 5017049.600").unwrap(),
+            (-45.0, 105.0),
+            1e-6,
+        );
+    }
+
+    #[test]
+    fn utm_round_trips_in_both_hemispheres() {
+        for (lat, lon) in SITES {
+            let (zone, band, easting, northing) = utm_forward(lat, lon);
+            let banded = format!("{}{} {:.3} {:.3}", zone, band, easting, northing);
+            let hemisphere = if lat >= 0.0 { "north" } else { "south" };
+            let spelled = format!("{} {} {:.3} {:.3}", zone, hemisphere, easting, northing);
+            if band != 'S' {
+                assert_close(parse_coordinate(&banded).unwrap(), (lat, lon), 1e-7);
+            }
+            assert_close(parse_coordinate(&spelled).unwrap(), (lat, lon), 1e-7);
+        }
+    }
+
+    #[test]
+    fn mgrs_round_trips_in_both_hemispheres() {
+        for (lat, lon) in SITES {
+            let (zone, band, easting, northing) = utm_forward(lat, lon);
+            let column = MGRS_COLUMN_LETTERS[((zone - 1) % 3) as usize]
+                .chars()
+                .nth((easting / 100000.0).floor() as usize - 1)
+                .unwrap();
+            let row = MGRS_ROW_LETTERS[((zone - 1) % 2) as usize]
+                .chars()
+                .nth((northing / 100000.0).floor() as usize % 20)
+                .unwrap();
+            let mgrs = format!(
+                "{}{}{}{} {:05} {:05}",
+                zone,
+                band,
+                column,
+                row,
+                (easting % 100000.0).floor(),
+                (northing % 100000.0).floor()
+            );
+            // 1 m squares are decoded to their centre.
+            assert_close(parse_coordinate(&mgrs).unwrap(), (lat, lon), 2e-5);
+        }
+    }
+
+    #[test]
+    fn ambiguous_utm_letters_are_rejected() {
+        // 48S reads as band S (32-40 N) or as the southern hemisphere.
+        assert!(matches!(
+            parse_coordinate("48S 500000 8500000"),
+            Err(CoordinateError::Malformed(_))
+        ));
+        // 48N with a northing outside band N (0-8 N).
+        let error = parse_coordinate("48N 500000 1600000").unwrap_err();
+        assert!(error.to_string().contains("48 north 500000 1600000"), "{}", error);
+        assert_eq!(
+            parse_coordinate("48 north 500000 1600000"),
+            utm_hemisphere_to_lat_lon(48, true, 500000.0, 1600000.0)
+        );
+    }
+
+    #[test]
+    fn out_of_range_inputs() {
+        assert_eq!(parse_coordinate("  "), Err(CoordinateError::Empty));
+        assert!(matches!(parse_coordinate("91, 0"), Err(CoordinateError::OutOfRange(_))));
+        assert!(matches!(parse_coordinate("61P 500000 1600000"), Err(CoordinateError::OutOfRange(_))));
+    }
+}
@@ -0,0 +1,503 @@
+use crate::analysis::{Analysis, TierKind, Verdict};
+use crate::geo::compass_point;
+use crate::monte_carlo::format_probability;
+use crate::report::{MODEL_REFERENCES, lat_lon};
+use crate::scenario::{Scenario, Site};
+use crate::weapon::UNSOURCED;
+
+/// Long-form report content, written out by `html` and `pdf`.
+pub struct Document {
+    pub title: String,
+    pub sections: Vec<Section>,
+}
+
+pub struct Section {
+    pub title: String,
+    pub blocks: Vec<Block>,
+}
+
+pub enum Block {
+    Paragraph(String),
+    /// A formula followed by what it computes.
+    Formula(String, String),
+    Table { header: Vec<String>, rows: Vec<Vec<String>> },
+    List(Vec<String>),
+    Chart(Chart),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Chart {
+    Trajectory,
+    RangeTiers,
+    Map,
+}
+
+impl Chart {
+    pub fn title(&self) -> &'static str {
+        match self {
+            Chart::Trajectory => "Trajectory side view",
+            Chart::RangeTiers => "Range tiers",
+            Chart::Map => "Map view",
+        }
+    }
+}
+
+fn table(header: &[&str], rows: Vec<Vec<String>>) -> Block {
+    Block::Table {
+        header: header.iter().map(|h| h.to_string()).collect(),
+        rows,
+    }
+}
+
+fn row(cells: &[&str]) -> Vec<String> {
+    cells.iter().map(|c| c.to_string()).collect()
+}
+
+fn km(meters: f64) -> String {
+    format!("{:.3} km", meters / 1000.0)
+}
+
+fn site_row(name: &str, site: &Site) -> Vec<String> {
+    let elevation = match site.elevation {
+        Some(elevation) => format!(", {:.0} m above sea level", elevation),
+        None => String::new(),
+    };
+    vec![
+        name.to_string(),
+        format!("{} (±{:.0} m){}", lat_lon(site.lat, site.lon), site.uncertainty, elevation),
+        site.source.clone().unwrap_or_else(|| UNSOURCED.to_string()),
+    ]
+}
+
+pub fn build(scenario: &Scenario, analysis: &Analysis) -> Document {
+    let weapon = &scenario.weapon;
+    let launch = &scenario.launch;
+    let target = &scenario.target;
+    let verdict = analysis.verdict();
+    let generous = analysis.most_generous_tier();
+    let cited = |field: &str| format!("{} [{}]", weapon.citation(field), weapon.source(field));
+
+    let summary = match verdict {
+        Verdict::Impossible => format!(
+            "{} lies {:.1} km from the claimed launch point in {}. That is {:.1}x the {:.1} km \
+             operational range of the {}, and {:.1} km beyond even the most generous range \
+             considered ({}, {:.1} km). On range alone the claimed strike cannot be made \
+             from there.",
+            target.label,
+            analysis.actual_distance / 1000.0,
+            launch.country,
+            analysis.range_multiplier,
+            weapon.max_range_operational / 1000.0,
+            weapon.name,
+            generous.shortfall / 1000.0,
+            generous.name,
+            generous.range / 1000.0
+        ),
+        _ => format!(
+            "{} lies {:.1} km from the claimed launch point in {}, against a {:.1} km \
+             operational range for the {}. Verdict: {}. See the range tiers for which \
+             assumptions reach the target.",
+            target.label,
+            analysis.actual_distance / 1000.0,
+            launch.country,
+            weapon.max_range_operational / 1000.0,
+            weapon.name,
+            verdict.label()
+        ),
+    };
+
+    let uncertainty = match &analysis.monte_carlo {
+        Some(mc) => format!(
+            "{} within operational range, {} within vacuum range ({} samples, seed {})",
+            format_probability(mc.probability_within_operational, mc.samples),
+            format_probability(mc.probability_within_vacuum, mc.samples),
+            mc.samples,
+            mc.seed
+        ),
+        None => "None given; the verdict is deterministic".to_string(),
+    };
+
+    let mut inputs = vec![site_row("Launch point", launch), site_row("Target point", target)];
+    inputs.push(vec!["Weapon".to_string(), weapon.description.clone(), String::new()]);
+    inputs.push(vec![
+        "Calibre".to_string(),
+        format!("{:.0} mm", weapon.calibre),
+        cited("calibre"),
+    ]);
+    inputs.push(vec![
+        "Projectile mass".to_string(),
+        format!("{:.1} kg", weapon.projectile_mass),
+        cited("projectile_mass"),
+    ]);
+    if let Some(mass) = weapon.warhead_mass {
+        inputs.push(vec!["Warhead mass".to_string(), format!("{:.1} kg", mass), cited("warhead_mass")]);
+    }
+    if let Some(length) = weapon.projectile_length {
+        inputs.push(vec!["Length".to_string(), format!("{:.2} m", length), cited("projectile_length")]);
+    }
+    inputs.push(vec![
+        "Muzzle velocity".to_string(),
+        format!("{:.0} m/s", weapon.muzzle_velocity),
+        cited("muzzle_velocity"),
+    ]);
+    inputs.push(vec![
+        "Operational range".to_string(),
+        km(weapon.max_range_operational),
+        cited("max_range_operational"),
+    ]);
+    inputs.push(vec![
+        "Published maximum range".to_string(),
+        km(weapon.max_range_45deg),
+        cited("max_range_45deg"),
+    ]);
+    for extended in &weapon.extended_ranges {
+        inputs.push(vec![
+            format!("Extended range: {}", extended.name),
+            km(extended.range),
+            format!(
+                "{} [{}]",
+                weapon.references.get(&extended.source).map(String::as_str).unwrap_or(UNSOURCED),
+                extended.source
+            ),
+        ]);
+    }
+    if let Some(motor) = weapon.motor {
+        inputs.push(vec!["Burn time".to_string(), format!("{:.2} s", motor.burn_time), cited("burn_time")]);
+        inputs.push(vec![
+            "Propellant mass".to_string(),
+            format!("{:.2} kg", motor.propellant_mass),
+            cited("propellant_mass"),
+        ]);
+        inputs.push(vec!["Thrust".to_string(), format!("{:.1} kN", motor.thrust / 1000.0), cited("thrust")]);
+    }
+    inputs.push(vec![
+        "Drag curve".to_string(),
+        format!("{} Mach/Cd points", weapon.drag_coefficients.len()),
+        cited("drag_coefficients"),
+    ]);
+    inputs.push(row(&["Gravity", &format!("{} m/s²", scenario.physics.gravity), "Scenario file"]));
+    inputs.push(row(&[
+        "Launch angle",
+        &format!("{:.1}°", scenario.physics.launch_angle),
+        "Scenario file",
+    ]));
+    inputs.push(row(&[
+        "Sea-level atmosphere",
+        &format!(
+            "{:.2} K, {:.0} Pa",
+            scenario.atmosphere.sea_level_temperature, scenario.atmosphere.sea_level_pressure
+        ),
+        "Scenario file, ICAO standard atmosphere layers",
+    ]));
+    inputs.push(row(&[
+        "Earth model",
+        analysis.earth_model.name(),
+        "Scenario file, R = 6,371 km (IUGG mean radius)",
+    ]));
+
+    let drag = analysis.drag();
+    let mut computed = vec![
+        row(&["Haversine distance", &km(analysis.actual_distance)]),
+        row(&[
+            "WGS84 geodesic distance",
+            &format!(
+                "{} ({})",
+                km(analysis.ellipsoidal.distance),
+                if analysis.ellipsoidal.converged { "Vincenty" } else { "spherical fallback" }
+            ),
+        ]),
+        row(&["Ellipsoidal minus spherical", &format!("{:+.1} m", analysis.ellipsoidal_difference())]),
+        row(&[
+            "Initial / final bearing",
+            &format!(
+                "{:.2}° ({}) / {:.2}° ({})",
+                analysis.initial_bearing,
+                compass_point(analysis.initial_bearing),
+                analysis.final_bearing,
+                compass_point(analysis.final_bearing)
+            ),
+        ]),
+        row(&[
+            "WGS84 forward / back azimuth",
+            &format!(
+                "{:.2}° / {:.2}°",
+                analysis.ellipsoidal.forward_azimuth, analysis.ellipsoidal.back_azimuth
+            ),
+        ]),
+        row(&[
+            "Path midpoint",
+            &format!("{:.6}, {:.6}", analysis.midpoint.0, analysis.midpoint.1),
+        ]),
+        row(&["Vacuum range", &km(analysis.range_theoretical)]),
+        row(&["Vacuum range on level ground", &km(analysis.trajectory.level_range())]),
+        row(&[
+            "Launch minus target altitude",
+            &format!("{:+.0} m ({:+.0} m of range)", analysis.trajectory.drop(), analysis.altitude_difference()),
+        ]),
+        row(&["Vacuum flight time", &format!("{:.1} s", analysis.t_flight)]),
+        row(&["Vacuum maximum height", &format!("{:.0} m", analysis.max_h)]),
+        row(&["Drag range", &km(drag.range)]),
+        row(&["Drag flight time", &format!("{:.1} s", drag.flight_time)]),
+        row(&["Drag maximum height", &format!("{:.0} m", drag.max_height)]),
+        row(&["Drag impact speed", &format!("{:.0} m/s", drag.impact_speed)]),
+    ];
+    if let Some(burnout) = drag.burnout {
+        computed.push(row(&[
+            "Motor burnout",
+            &format!(
+                "{:.0} m/s after {:.2} s, {:.0} m out, {:.0} m up",
+                burnout.speed,
+                burnout.time,
+                burnout.range,
+                burnout.altitude - analysis.altitudes.launch
+            ),
+        ]));
+    }
+    computed.extend([
+        row(&["Range lost to drag", &format!("{:.0}%", analysis.drag_range_loss() * 100.0)]),
+        row(&[
+            "Flat vs round Earth drag range",
+            &format!(
+                "{} vs {} ({:+.0} m)",
+                km(analysis.drag_flat.range),
+                km(analysis.drag_spherical.range),
+                analysis.curvature_difference()
+            ),
+        ]),
+        row(&["Shortfall vs operational range", &km(analysis.range_shortfall)]),
+        row(&["Distance / operational range", &format!("{:.2}x", analysis.range_multiplier)]),
+    ]);
+
+    let tier_rows = analysis
+        .tiers
+        .iter()
+        .map(|tier| {
+            vec![
+                tier.name.clone(),
+                match tier.kind {
+                    TierKind::Declared => "declared".to_string(),
+                    TierKind::Physics => "physics".to_string(),
+                },
+                km(tier.range),
+                km(tier.shortfall.max(0.0)),
+                format!("{:.2}x", tier.multiplier),
+                format!(
+                    "{}{}",
+                    if tier.reaches() { "reaches" } else { "out of range" },
+                    if tier.in_verdict { "" } else { " (not counted, unsourced)" }
+                ),
+            ]
+        })
+        .collect();
+
+    // Only what this analysis cites: the selected weapon's sources and the
+    // model constants.
+    let mut references: Vec<String> = weapon
+        .references
+        .iter()
+        .map(|(key, citation)| format!("[{}] {}", key, citation))
+        .collect();
+    for input in weapon.unsourced_drag_inputs() {
+        references.push(format!("[{}] Estimated {}, no published source", UNSOURCED, input));
+    }
+    references.extend(MODEL_REFERENCES.iter().map(|(key, citation)| format!("[{}] {}", key, citation)));
+
+    let mut trajectory_blocks = vec![Block::Paragraph(format!(
+        "Launch at {:.0}° and {:.0} m/s, in vacuum and with Mach-dependent drag \
+         through the ISA atmosphere on a {} Earth.",
+        scenario.physics.launch_angle,
+        weapon.muzzle_velocity,
+        analysis.earth_model.name()
+    ))];
+    if scenario.terrain_profile.is_some() {
+        let impact = analysis.drag().impact();
+        trajectory_blocks.push(Block::Paragraph(match impact.unblocked_range {
+            Some(unblocked) => format!(
+                "Over the SRTM terrain the drag path meets a ridge {} out at {:.0} m, short of \
+                 the {} it would otherwise reach.",
+                km(impact.range),
+                impact.elevation,
+                km(unblocked)
+            ),
+            None => format!(
+                "Over the SRTM terrain the drag path comes down {} out at {:.0} m; no ridge \
+                 along the way blocks it.",
+                km(impact.range),
+                impact.elevation
+            ),
+        }));
+    }
+    trajectory_blocks.push(Block::Chart(Chart::Trajectory));
+
+    Document {
+        title: format!(
+            "{} range analysis: {} to {}",
+            weapon.name, launch.label, target.label
+        ),
+        sections: vec![
+            Section {
+                title: "Summary".to_string(),
+                blocks: vec![
+                    Block::Paragraph(summary),
+                    table(
+                        &["Quantity", "Value"],
+                        vec![
+                            row(&["Required distance", &km(analysis.actual_distance)]),
+                            row(&["Operational range", &km(weapon.max_range_operational)]),
+                            row(&["Most generous range", &format!("{} ({})", km(generous.range), generous.name)]),
+                            row(&["Verdict", verdict.label()]),
+                            row(&["Monte Carlo", &uncertainty]),
+                        ],
+                    ),
+                ],
+            },
+            Section {
+                title: "Inputs and sources".to_string(),
+                blocks: vec![table(&["Input", "Value", "Source"], inputs)],
+            },
+            Section {
+                title: "Formulas".to_string(),
+                blocks: vec![
+                    Block::Formula(
+                        "d = 2R · arcsin(√(sin²(Δφ/2) + cos φ₁ · cos φ₂ · sin²(Δλ/2)))".to_string(),
+                        "Haversine great-circle distance on a sphere of radius R = 6,371 km.".to_string(),
+                    ),
+                    Block::Formula(
+                        "θ = atan2(sin Δλ · cos φ₂, cos φ₁ · sin φ₂ − sin φ₁ · cos φ₂ · cos Δλ)".to_string(),
+                        "Initial bearing; the final bearing is the reverse bearing turned 180°.".to_string(),
+                    ),
+                    Block::Formula(
+                        "Vincenty inverse on the WGS84 ellipsoid (a = 6,378,137 m, f = 1/298.257223563)".to_string(),
+                        "Ellipsoidal geodesic distance and azimuths, iterated to 1e-12 rad.".to_string(),
+                    ),
+                    Block::Formula(
+                        "T = (v₀ · sin θ + √((v₀ · sin θ)² + 2g · Δh)) / g,   R = v₀ · cos θ · T".to_string(),
+                        "Vacuum flight time and range from the launch altitude down to the target \
+                         altitude, Δh being the launch altitude minus the target altitude."
+                            .to_string(),
+                    ),
+                    Block::Formula(
+                        "R = v₀² · sin(2θ) / g,   H = (v₀ · sin θ)² / (2g)".to_string(),
+                        "Vacuum range with Δh = 0, and maximum height above the launch site.".to_string(),
+                    ),
+                    Block::Formula(
+                        "a = (F(t) · v / |v| − ρ(h) · Cd(M) · A · |v| · v / 2) / m(t) − g".to_string(),
+                        "Point-mass equations of motion with air drag, integrated with fourth-order \
+                         Runge-Kutta; Cd depends on Mach number M = |v| / a(h). For a rocket the \
+                         motor thrust F and the mass m(t) = m₀ − ṁ · t hold only until burnout, \
+                         after which F = 0 and the flight is ballistic."
+                            .to_string(),
+                    ),
+                    Block::Formula(
+                        "T(h) = T₀ + L·h,   p(h) = p₀ · (T(h)/T₀)^(−g₀M/(R*L)),   ρ = p / (R_air · T)".to_string(),
+                        "ICAO standard atmosphere, layer by layer up to 84.852 km.".to_string(),
+                    ),
+                ],
+            },
+            Section {
+                title: "Computed values".to_string(),
+                blocks: vec![table(&["Quantity", "Value"], computed)],
+            },
+            Section {
+                title: "Range tiers".to_string(),
+                blocks: vec![
+                    Block::Paragraph(
+                        "The required distance is checked against every declared range and \
+                         against the physics models, shortest first."
+                            .to_string(),
+                    ),
+                    table(
+                        &["Tier", "Kind", "Range", "Shortfall", "Factor", "Result"],
+                        tier_rows,
+                    ),
+                    Block::Chart(Chart::RangeTiers),
+                ],
+            },
+            Section {
+                title: "Map".to_string(),
+                blocks: vec![
+                    Block::Paragraph(format!(
+                        "Range rings around the launch site and the great-circle path to the \
+                         target, in a {} projection centred on the launch site.",
+                        scenario.map.projection.name()
+                    )),
+                    Block::Chart(Chart::Map),
+                ],
+            },
+            Section {
+                title: "Trajectory".to_string(),
+                blocks: trajectory_blocks,
+            },
+            Section {
+                title: "Model assumptions".to_string(),
+                blocks: vec![Block::List(vec![
+                    match weapon.motor {
+                        Some(motor) => format!(
+                            "Point-mass rocket launched from rest, constant {:.1} kN thrust and mass \
+                             flow for {:.2} s, no lift or spin",
+                            motor.thrust / 1000.0,
+                            motor.burn_time
+                        ),
+                        None => "Point-mass projectile, no lift, spin or thrust after burnout".to_string(),
+                    },
+                    "No wind; ISA temperature and pressure profile".to_string(),
+                    format!(
+                        "Gravity {} m/s², {} Earth of radius 6,371 km",
+                        scenario.physics.gravity,
+                        analysis.earth_model.name()
+                    ),
+                    match &scenario.terrain_profile {
+                        Some(_) => format!(
+                            "Launch at {:.0} m and impact at {:.0} m above sea level, from SRTM terrain",
+                            analysis.altitudes.launch, analysis.altitudes.impact
+                        ),
+                        None if launch.elevation.is_some() || target.elevation.is_some() => format!(
+                            "Launch at {:.0} m and impact at {:.0} m above sea level",
+                            analysis.altitudes.launch, analysis.altitudes.impact
+                        ),
+                        None => "Launch and impact at sea level".to_string(),
+                    },
+                ])],
+            },
+            Section {
+                title: "References".to_string(),
+                blocks: vec![Block::List(references)],
+            },
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn references_list_only_what_the_weapon_cites() {
+        let scenario = Scenario::default();
+        let analysis = Analysis::new(&scenario).unwrap();
+        let document = build(&scenario, &analysis);
+        let section = document.sections.iter().find(|s| s.title == "References").unwrap();
+        let [Block::List(references)] = section.blocks.as_slice() else {
+            panic!("references are one list");
+        };
+
+        for (key, citation) in &scenario.weapon.references {
+            assert!(references.contains(&format!("[{}] {}", key, citation)));
+        }
+        for (key, _) in MODEL_REFERENCES {
+            assert!(references.iter().any(|r| r.starts_with(&format!("[{}]", key))));
+        }
+        let expected = scenario.weapon.references.len()
+            + MODEL_REFERENCES.len()
+            + scenario.weapon.unsourced_drag_inputs().len();
+        assert_eq!(references.len(), expected);
+    }
+
+    #[test]
+    fn site_rows_name_the_hemispheres() {
+        let mut site = Scenario::default().launch;
+        (site.lat, site.lon) = (-33.9, -70.65);
+        site.elevation = None;
+        assert!(site_row("Launch", &site)[1].starts_with("33.900000S, 70.650000W (±"));
+    }
+}
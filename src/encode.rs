@@ -0,0 +1,334 @@
+use opencv::{core, imgproc, prelude::*, videoio};
+use serde::Deserialize;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+/// Container and codec of the rendered animation.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VideoFormat {
+    /// MPEG-4 Part 2 in MP4, plays nearly everywhere.
+    Mp4,
+    /// H.264 in MP4, needs an OpenCV build with an H.264 encoder.
+    H264,
+    /// VP9 in WebM.
+    WebM,
+    /// Animated GIF with a quantised palette per frame.
+    Gif,
+    /// Animated PNG, lossless.
+    Apng,
+    /// Numbered PNG files in a directory.
+    Frames,
+}
+
+pub const VIDEO_FORMATS: [(&str, VideoFormat); 6] = [
+    ("mp4", VideoFormat::Mp4),
+    ("h264", VideoFormat::H264),
+    ("webm", VideoFormat::WebM),
+    ("gif", VideoFormat::Gif),
+    ("apng", VideoFormat::Apng),
+    ("frames", VideoFormat::Frames),
+];
+
+impl VideoFormat {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        let name = name.trim().to_lowercase();
+        VIDEO_FORMATS
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, format)| *format)
+            .ok_or_else(|| {
+                let names: Vec<&str> = VIDEO_FORMATS.iter().map(|(n, _)| *n).collect();
+                format!("unknown video format \"{}\", use one of: {}", name, names.join(", "))
+            })
+    }
+
+    /// Format implied by the extension; a path without one is a directory
+    /// for an image sequence.
+    pub fn from_path(path: &Path) -> Result<Self, String> {
+        let extension = match path.extension().and_then(|e| e.to_str()) {
+            Some(extension) => extension.to_lowercase(),
+            None => return Ok(VideoFormat::Frames),
+        };
+        match extension.as_str() {
+            "mp4" | "m4v" => Ok(VideoFormat::Mp4),
+            "webm" => Ok(VideoFormat::WebM),
+            "gif" => Ok(VideoFormat::Gif),
+            "png" | "apng" => Ok(VideoFormat::Apng),
+            _ => Err(format!(
+                "cannot tell the video format of {}, use .mp4, .webm, .gif, .png or --format",
+                path.display()
+            )),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        VIDEO_FORMATS
+            .iter()
+            .find(|(_, format)| format == self)
+            .map(|(name, _)| *name)
+            .unwrap_or("mp4")
+    }
+}
+
+/// Frame size and timing shared by every sink.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameFormat {
+    pub width: u32,
+    pub height: u32,
+    pub fps: usize,
+}
+
+/// Receives rendered frames in order as packed 8-bit RGB.
+pub trait FrameSink {
+    /// Appends a frame shown for `periods` frame periods.
+    fn write(&mut self, rgb: &[u8], periods: usize) -> Result<(), Box<dyn std::error::Error>>;
+
+    fn finish(self: Box<Self>) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+/// Opens a sink for `format` at `path`. `frames` is the number of `write`
+/// calls that will follow, which APNG needs up front.
+pub fn open(
+    format: VideoFormat,
+    path: &Path,
+    frame: FrameFormat,
+    frames: usize,
+) -> Result<Box<dyn FrameSink>, Box<dyn std::error::Error>> {
+    Ok(match format {
+        VideoFormat::Mp4 => Box::new(VideoSink::open(path, ('m', 'p', '4', 'v'), frame)?),
+        VideoFormat::H264 => Box::new(VideoSink::open(path, ('a', 'v', 'c', '1'), frame)?),
+        VideoFormat::WebM => Box::new(VideoSink::open(path, ('V', 'P', '9', '0'), frame)?),
+        VideoFormat::Gif => Box::new(GifSink::open(path, frame)?),
+        VideoFormat::Apng => Box::new(ApngSink::open(path, frame, frames)?),
+        VideoFormat::Frames => Box::new(SequenceSink::open(path, frame)?),
+    })
+}
+
+/// Hundredths of a second, rounded, from the start to the end of `periods`
+/// frames at `fps`.
+fn gif_centiseconds(periods: usize, fps: usize) -> usize {
+    (periods * 100 + fps / 2) / fps
+}
+
+/// Converts a packed RGB buffer into a BGR `Mat`.
+pub fn rgb_to_mat(rgb: &[u8], height: u32) -> Result<core::Mat, Box<dyn std::error::Error>> {
+    let mat = core::Mat::from_slice(rgb)?;
+    let mat = mat.reshape(3, height as i32)?;
+    let mut bgr = core::Mat::default();
+    imgproc::cvt_color_def(&mat, &mut bgr, imgproc::COLOR_RGB2BGR)?;
+    Ok(bgr)
+}
+
+/// Converts a BGR `Mat` into a packed RGB buffer.
+pub fn mat_to_rgb(bgr: &core::Mat) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut rgb = core::Mat::default();
+    imgproc::cvt_color_def(bgr, &mut rgb, imgproc::COLOR_BGR2RGB)?;
+    Ok(rgb.data_bytes()?.to_vec())
+}
+
+/// Writes one RGB frame as a PNG file.
+pub fn write_png(path: &Path, rgb: &[u8], width: u32, height: u32) -> Result<(), Box<dyn std::error::Error>> {
+    let file = File::create(path).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_adaptive_filter(png::AdaptiveFilterType::Adaptive);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(rgb)?;
+    writer.finish()?;
+    Ok(())
+}
+
+struct VideoSink {
+    writer: videoio::VideoWriter,
+    height: u32,
+}
+
+impl VideoSink {
+    fn open(
+        path: &Path,
+        fourcc: (char, char, char, char),
+        frame: FrameFormat,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let (a, b, c, d) = fourcc;
+        let writer = videoio::VideoWriter::new(
+            &path.to_string_lossy(),
+            videoio::VideoWriter::fourcc(a, b, c, d)?,
+            frame.fps as f64,
+            core::Size::new(frame.width as i32, frame.height as i32),
+            true,
+        )?;
+        if !writer.is_opened()? {
+            return Err(format!(
+                "cannot open {} with the {}{}{}{} codec, this OpenCV build may lack the encoder",
+                path.display(),
+                a,
+                b,
+                c,
+                d
+            )
+            .into());
+        }
+        Ok(VideoSink {
+            writer,
+            height: frame.height,
+        })
+    }
+}
+
+impl FrameSink for VideoSink {
+    fn write(&mut self, rgb: &[u8], periods: usize) -> Result<(), Box<dyn std::error::Error>> {
+        let frame = rgb_to_mat(rgb, self.height)?;
+        for _ in 0..periods {
+            self.writer.write(&frame)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), Box<dyn std::error::Error>> {
+        self.writer.release()?;
+        Ok(())
+    }
+}
+
+/// NeuQuant sampling factor for the GIF palettes, 1 (best) to 30 (fastest).
+const GIF_QUANTIZE_SPEED: i32 = 10;
+
+struct GifSink {
+    encoder: gif::Encoder<BufWriter<File>>,
+    frame: FrameFormat,
+    /// Frame periods and hundredths of a second written so far, so that
+    /// rounded delays add up to the total duration.
+    periods: usize,
+    centiseconds: usize,
+}
+
+impl GifSink {
+    fn open(path: &Path, frame: FrameFormat) -> Result<Self, Box<dyn std::error::Error>> {
+        let (width, height) = (u16::try_from(frame.width)?, u16::try_from(frame.height)?);
+        let file = File::create(path).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+        let mut encoder = gif::Encoder::new(BufWriter::new(file), width, height, &[])?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+        Ok(GifSink {
+            encoder,
+            frame,
+            periods: 0,
+            centiseconds: 0,
+        })
+    }
+}
+
+impl FrameSink for GifSink {
+    fn write(&mut self, rgb: &[u8], periods: usize) -> Result<(), Box<dyn std::error::Error>> {
+        let mut frame = gif::Frame::from_rgb_speed(
+            self.frame.width as u16,
+            self.frame.height as u16,
+            rgb,
+            GIF_QUANTIZE_SPEED,
+        );
+        // GIF delays are in hundredths of a second; each frame ends at the
+        // rounded end time, carrying the remainder on to the next.
+        self.periods += periods;
+        let end = gif_centiseconds(self.periods, self.frame.fps);
+        frame.delay = (end - self.centiseconds).min(u16::MAX as usize) as u16;
+        self.centiseconds += frame.delay as usize;
+        self.encoder.write_frame(&frame)?;
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), Box<dyn std::error::Error>> {
+        // The trailer is written when the encoder is dropped.
+        drop(self.encoder);
+        Ok(())
+    }
+}
+
+struct ApngSink {
+    writer: png::Writer<BufWriter<File>>,
+    fps: u16,
+}
+
+impl ApngSink {
+    fn open(path: &Path, frame: FrameFormat, frames: usize) -> Result<Self, Box<dyn std::error::Error>> {
+        let file = File::create(path).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+        let mut encoder = png::Encoder::new(BufWriter::new(file), frame.width, frame.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_adaptive_filter(png::AdaptiveFilterType::Adaptive);
+        encoder.set_animated(u32::try_from(frames)?, 0)?;
+        Ok(ApngSink {
+            writer: encoder.write_header()?,
+            fps: u16::try_from(frame.fps)?,
+        })
+    }
+}
+
+impl FrameSink for ApngSink {
+    fn write(&mut self, rgb: &[u8], periods: usize) -> Result<(), Box<dyn std::error::Error>> {
+        self.writer.set_frame_delay(u16::try_from(periods)?, self.fps)?;
+        self.writer.write_image_data(rgb)?;
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), Box<dyn std::error::Error>> {
+        self.writer.finish()?;
+        Ok(())
+    }
+}
+
+/// `frame_00000.png`, `frame_00001.png`, ... at the video frame rate, so a
+/// frame shown for several periods is repeated.
+struct SequenceSink {
+    dir: PathBuf,
+    frame: FrameFormat,
+    index: usize,
+}
+
+impl SequenceSink {
+    fn open(dir: &Path, frame: FrameFormat) -> Result<Self, Box<dyn std::error::Error>> {
+        fs::create_dir_all(dir).map_err(|e| format!("cannot create {}: {}", dir.display(), e))?;
+        Ok(SequenceSink {
+            dir: dir.to_path_buf(),
+            frame,
+            index: 0,
+        })
+    }
+}
+
+impl FrameSink for SequenceSink {
+    fn write(&mut self, rgb: &[u8], periods: usize) -> Result<(), Box<dyn std::error::Error>> {
+        for _ in 0..periods {
+            let path = self.dir.join(format!("frame_{:05}.png", self.index));
+            write_png(&path, rgb, self.frame.width, self.frame.height)?;
+            self.index += 1;
+        }
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gif_delays_add_up_to_the_duration() {
+        // 15 fps does not divide 100; the delays run 7, 6, 7 cs.
+        let mut written = 0;
+        let delays: Vec<usize> = (1..=225)
+            .map(|periods| {
+                let delay = gif_centiseconds(periods, 15) - written;
+                written += delay;
+                delay
+            })
+            .collect();
+        assert_eq!(&delays[..3], &[7, 6, 7]);
+        assert_eq!(written, 1500);
+        assert_eq!(gif_centiseconds(3, 30), 10);
+    }
+}
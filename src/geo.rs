@@ -0,0 +1,247 @@
+use serde::Serialize;
+use std::f64::consts::PI;
+
+pub const EARTH_RADIUS: f64 = 6371000.0;
+
+pub const WGS84_A: f64 = 6378137.0;
+pub const WGS84_F: f64 = 1.0 / 298.257223563;
+pub const WGS84_B: f64 = WGS84_A * (1.0 - WGS84_F);
+
+const VINCENTY_TOLERANCE: f64 = 1e-12;
+const VINCENTY_MAX_ITERATIONS: usize = 200;
+
+/// Ellipsoidal geodesic between two points. Azimuths are in degrees clockwise
+/// from north; the back azimuth points from the second point to the first.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Geodesic {
+    pub distance: f64,
+    pub forward_azimuth: f64,
+    pub back_azimuth: f64,
+    /// False when Vincenty's iteration failed (nearly antipodal points) and
+    /// the spherical solution was used instead.
+    pub converged: bool,
+}
+
+pub fn haversine_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let lat1_rad = lat1.to_radians();
+    let lat2_rad = lat2.to_radians();
+    let delta_lat = (lat2 - lat1).to_radians();
+    let delta_lon = (lon2 - lon1).to_radians();
+
+    let a = (delta_lat / 2.0).sin().powi(2)
+        + lat1_rad.cos() * lat2_rad.cos() * (delta_lon / 2.0).sin().powi(2);
+    let c = 2.0 * a.sqrt().atan2((1.0 - a).sqrt());
+
+    EARTH_RADIUS * c
+}
+
+/// Great-circle bearing at the first point towards the second, in degrees
+/// clockwise from north.
+pub fn initial_bearing(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let delta_lon = (lon2 - lon1).to_radians();
+    let y = delta_lon.sin() * phi2.cos();
+    let x = phi1.cos() * phi2.sin() - phi1.sin() * phi2.cos() * delta_lon.cos();
+    normalize_bearing(y.atan2(x).to_degrees())
+}
+
+/// Great-circle bearing of travel on arrival at the second point.
+pub fn final_bearing(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    normalize_bearing(initial_bearing(lat2, lon2, lat1, lon1) + 180.0)
+}
+
+/// Point halfway along the great-circle path, as (lat, lon) in degrees.
+pub fn midpoint(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> (f64, f64) {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let lambda1 = lon1.to_radians();
+    let delta_lon = (lon2 - lon1).to_radians();
+    let bx = phi2.cos() * delta_lon.cos();
+    let by = phi2.cos() * delta_lon.sin();
+    let phi_m = (phi1.sin() + phi2.sin()).atan2(((phi1.cos() + bx).powi(2) + by.powi(2)).sqrt());
+    let lambda_m = lambda1 + by.atan2(phi1.cos() + bx);
+    (phi_m.to_degrees(), (lambda_m.to_degrees() + 540.0).rem_euclid(360.0) - 180.0)
+}
+
+/// Point reached by travelling `distance` meters along a great circle from
+/// `(lat, lon)` with the given initial bearing in degrees.
+pub fn destination_point(lat: f64, lon: f64, bearing: f64, distance: f64) -> (f64, f64) {
+    let phi1 = lat.to_radians();
+    let lambda1 = lon.to_radians();
+    let theta = bearing.to_radians();
+    let delta = distance / EARTH_RADIUS;
+
+    let sin_phi2 = phi1.sin() * delta.cos() + phi1.cos() * delta.sin() * theta.cos();
+    let phi2 = sin_phi2.asin();
+    let lambda2 = lambda1
+        + (theta.sin() * delta.sin() * phi1.cos()).atan2(delta.cos() - phi1.sin() * sin_phi2);
+    (phi2.to_degrees(), (lambda2.to_degrees() + 540.0).rem_euclid(360.0) - 180.0)
+}
+
+/// 16-point compass name for a bearing in degrees.
+pub fn compass_point(bearing: f64) -> &'static str {
+    const POINTS: [&str; 16] = [
+        "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW",
+        "NW", "NNW",
+    ];
+    POINTS[((normalize_bearing(bearing) / 22.5).round() as usize) % 16]
+}
+
+/// Vincenty's inverse solution on the WGS84 ellipsoid. Falls back to the
+/// spherical great circle when the iteration does not converge.
+pub fn vincenty_inverse(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> Geodesic {
+    let f = WGS84_F;
+    // Wrapped into [-180, 180) so paths across the antimeridian start
+    // from the short way round.
+    let l = ((lon2 - lon1 + 540.0).rem_euclid(360.0) - 180.0).to_radians();
+    let u1 = ((1.0 - f) * lat1.to_radians().tan()).atan();
+    let u2 = ((1.0 - f) * lat2.to_radians().tan()).atan();
+    let (sin_u1, cos_u1) = u1.sin_cos();
+    let (sin_u2, cos_u2) = u2.sin_cos();
+
+    let mut lambda = l;
+    for _ in 0..VINCENTY_MAX_ITERATIONS {
+        let (sin_lambda, cos_lambda) = lambda.sin_cos();
+        let sin_sigma = ((cos_u2 * sin_lambda).powi(2)
+            + (cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda).powi(2))
+        .sqrt();
+        if sin_sigma == 0.0 {
+            return Geodesic {
+                distance: 0.0,
+                forward_azimuth: 0.0,
+                back_azimuth: 0.0,
+                converged: true,
+            };
+        }
+        let cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+        let sigma = sin_sigma.atan2(cos_sigma);
+        let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+        let cos_sq_alpha = 1.0 - sin_alpha.powi(2);
+        let cos_2sigma_m = if cos_sq_alpha != 0.0 {
+            cos_sigma - 2.0 * sin_u1 * sin_u2 / cos_sq_alpha
+        } else {
+            0.0 // equatorial line
+        };
+        let c = f / 16.0 * cos_sq_alpha * (4.0 + f * (4.0 - 3.0 * cos_sq_alpha));
+
+        let lambda_prev = lambda;
+        lambda = l
+            + (1.0 - c)
+                * f
+                * sin_alpha
+                * (sigma
+                    + c * sin_sigma
+                        * (cos_2sigma_m + c * cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))));
+
+        if lambda.abs() > PI {
+            break;
+        }
+        if (lambda - lambda_prev).abs() < VINCENTY_TOLERANCE {
+            let u_sq = cos_sq_alpha * (WGS84_A.powi(2) - WGS84_B.powi(2)) / WGS84_B.powi(2);
+            let a = 1.0
+                + u_sq / 16384.0 * (4096.0 + u_sq * (-768.0 + u_sq * (320.0 - 175.0 * u_sq)));
+            let b = u_sq / 1024.0 * (256.0 + u_sq * (-128.0 + u_sq * (74.0 - 47.0 * u_sq)));
+            let delta_sigma = b
+                * sin_sigma
+                * (cos_2sigma_m
+                    + b / 4.0
+                        * (cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))
+                            - b / 6.0
+                                * cos_2sigma_m
+                                * (-3.0 + 4.0 * sin_sigma.powi(2))
+                                * (-3.0 + 4.0 * cos_2sigma_m.powi(2))));
+
+            let (sin_lambda, cos_lambda) = lambda.sin_cos();
+            let alpha1 = (cos_u2 * sin_lambda).atan2(cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda);
+            let alpha2 = (cos_u1 * sin_lambda).atan2(-sin_u1 * cos_u2 + cos_u1 * sin_u2 * cos_lambda);
+
+            return Geodesic {
+                distance: WGS84_B * a * (sigma - delta_sigma),
+                forward_azimuth: normalize_bearing(alpha1.to_degrees()),
+                back_azimuth: normalize_bearing(alpha2.to_degrees() + 180.0),
+                converged: true,
+            };
+        }
+    }
+
+    spherical_geodesic(lat1, lon1, lat2, lon2)
+}
+
+fn spherical_geodesic(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> Geodesic {
+    Geodesic {
+        distance: haversine_distance(lat1, lon1, lat2, lon2),
+        forward_azimuth: initial_bearing(lat1, lon1, lat2, lon2),
+        back_azimuth: initial_bearing(lat2, lon2, lat1, lon1),
+        converged: false,
+    }
+}
+
+fn normalize_bearing(degrees: f64) -> f64 {
+    degrees.rem_euclid(360.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dms(degrees: f64, minutes: f64, seconds: f64) -> f64 {
+        degrees.signum() * (degrees.abs() + minutes / 60.0 + seconds / 3600.0)
+    }
+
+    #[test]
+    fn vincenty_flinders_peak_to_buninyong() {
+        // Geoscience Australia's worked example on GRS80, which matches
+        // WGS84 to well under a millimetre over this line.
+        let g = vincenty_inverse(
+            dms(-37.0, 57.0, 3.72030),
+            dms(144.0, 25.0, 29.52440),
+            dms(-37.0, 39.0, 10.15610),
+            dms(143.0, 55.0, 35.38390),
+        );
+        assert!(g.converged);
+        assert!((g.distance - 54972.271).abs() < 0.001, "{}", g.distance);
+        assert!((g.forward_azimuth - dms(306.0, 52.0, 5.37)).abs() < 1.0 / 3600.0);
+        assert!((g.back_azimuth - dms(127.0, 10.0, 25.07)).abs() < 1.0 / 3600.0);
+    }
+
+    #[test]
+    fn vincenty_along_the_equator() {
+        let g = vincenty_inverse(0.0, 0.0, 0.0, 1.0);
+        assert!(g.converged);
+        assert!((g.distance - WGS84_A * 1f64.to_radians()).abs() < 1e-6);
+        assert!((g.forward_azimuth - 90.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn vincenty_across_the_antimeridian() {
+        let g = vincenty_inverse(0.0, 179.9, 0.0, -179.9);
+        assert!(g.converged);
+        assert!((g.distance - WGS84_A * 0.2f64.to_radians()).abs() < 1e-4);
+        assert!((g.forward_azimuth - 90.0).abs() < 1e-9);
+
+        let across = vincenty_inverse(10.0, 179.9, 10.5, -179.8);
+        let shifted = vincenty_inverse(10.0, -0.1, 10.5, 0.2);
+        assert!(across.converged);
+        assert!((across.distance - shifted.distance).abs() < 1e-4);
+        assert!((across.forward_azimuth - shifted.forward_azimuth).abs() < 1e-6);
+    }
+
+    #[test]
+    fn vincenty_nearly_antipodal_falls_back_to_the_sphere() {
+        let g = vincenty_inverse(0.0, 0.0, 0.5, 179.7);
+        assert!(!g.converged);
+        assert_eq!(g.distance, haversine_distance(0.0, 0.0, 0.5, 179.7));
+    }
+
+    #[test]
+    fn haversine_quarter_meridian() {
+        let d = haversine_distance(0.0, 0.0, 90.0, 0.0);
+        assert!((d - EARTH_RADIUS * PI / 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn destination_point_inverts_bearing_and_distance() {
+        let (lat, lon) = destination_point(14.3559, 103.2586, 240.0, 250_000.0);
+        assert!((haversine_distance(14.3559, 103.2586, lat, lon) - 250_000.0).abs() < 1e-6);
+        assert!((initial_bearing(14.3559, 103.2586, lat, lon) - 240.0).abs() < 1e-9);
+    }
+}
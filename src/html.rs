@@ -0,0 +1,113 @@
+use plotters::prelude::*;
+
+use crate::analysis::Analysis;
+use crate::document::{self, Block, Chart};
+use crate::render;
+use crate::scenario::Scenario;
+
+const CHART_SIZE: (u32, u32) = (1200, 675);
+
+const STYLE: &str = "
+body { font-family: Georgia, serif; max-width: 60em; margin: 2em auto; padding: 0 1em; color: #222; line-height: 1.5; }
+h1 { font-size: 1.8em; border-bottom: 2px solid #b00; padding-bottom: 0.3em; }
+h2 { font-size: 1.3em; margin-top: 2em; color: #b00; }
+table { border-collapse: collapse; width: 100%; margin: 1em 0; font-size: 0.95em; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; vertical-align: top; }
+th { background: #f0f0f8; }
+.formula { font-family: 'DejaVu Sans Mono', monospace; background: #f6f6f6; padding: 0.5em 1em; margin: 1em 0 0.2em; }
+.formula + p { margin-top: 0; color: #555; }
+figure { margin: 1.5em 0; }
+figure svg { width: 100%; height: auto; }
+figcaption { color: #555; font-style: italic; }
+footer { margin-top: 3em; font-size: 0.85em; color: #777; }
+";
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn chart_svg(
+    chart: Chart,
+    scenario: &Scenario,
+    analysis: &Analysis,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut svg = String::new();
+    {
+        let root = SVGBackend::with_string(&mut svg, CHART_SIZE).into_drawing_area();
+        match chart {
+            Chart::Trajectory => render::trajectory_chart(&root, scenario, analysis)?,
+            Chart::RangeTiers => render::tier_chart(&root, analysis)?,
+            Chart::Map => render::map_chart(&root, scenario, analysis)?,
+        }
+    }
+    Ok(svg)
+}
+
+/// Self-contained HTML report: styles inline and charts as inline SVG.
+pub fn html(scenario: &Scenario, analysis: &Analysis) -> Result<String, Box<dyn std::error::Error>> {
+    let doc = document::build(scenario, analysis);
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str(&format!("<title>{}</title>\n", escape(&doc.title)));
+    out.push_str(&format!("<style>{}</style>\n</head>\n<body>\n", STYLE));
+    out.push_str(&format!("<h1>{}</h1>\n", escape(&doc.title)));
+
+    for section in &doc.sections {
+        out.push_str(&format!("<h2>{}</h2>\n", escape(&section.title)));
+        for block in &section.blocks {
+            match block {
+                Block::Paragraph(text) => out.push_str(&format!("<p>{}</p>\n", escape(text))),
+                Block::Formula(formula, meaning) => out.push_str(&format!(
+                    "<div class=\"formula\">{}</div>\n<p>{}</p>\n",
+                    escape(formula),
+                    escape(meaning)
+                )),
+                Block::Table { header, rows } => {
+                    out.push_str("<table>\n<tr>");
+                    for cell in header {
+                        out.push_str(&format!("<th>{}</th>", escape(cell)));
+                    }
+                    out.push_str("</tr>\n");
+                    for row in rows {
+                        out.push_str("<tr>");
+                        for cell in row {
+                            out.push_str(&format!("<td>{}</td>", escape(cell)));
+                        }
+                        out.push_str("</tr>\n");
+                    }
+                    out.push_str("</table>\n");
+                }
+                Block::List(items) => {
+                    out.push_str("<ul>\n");
+                    for item in items {
+                        out.push_str(&format!("<li>{}</li>\n", escape(item)));
+                    }
+                    out.push_str("</ul>\n");
+                }
+                Block::Chart(chart) => out.push_str(&format!(
+                    "<figure>\n{}\n<figcaption>{}</figcaption>\n</figure>\n",
+                    chart_svg(*chart, scenario, analysis)?,
+                    escape(chart.title())
+                )),
+            }
+        }
+    }
+
+    out.push_str(&format!(
+        "<footer>Generated by {} {}</footer>\n</body>\n</html>\n",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    ));
+    Ok(out)
+}
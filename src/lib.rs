@@ -0,0 +1,23 @@
+pub mod analysis;
+pub mod atmosphere;
+pub mod ballistics;
+pub mod basemap;
+pub mod coords;
+pub mod document;
+pub mod encode;
+pub mod geo;
+pub mod html;
+pub mod map;
+pub mod markdown;
+pub mod monte_carlo;
+pub mod pdf;
+pub mod render;
+pub mod report;
+pub mod results;
+pub mod scenario;
+pub mod terrain;
+pub mod weapon;
+
+pub use analysis::Analysis;
+pub use scenario::Scenario;
+pub use weapon::{Catalog, WeaponSpecs};
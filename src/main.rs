@@ -1,668 +1,371 @@
-use opencv::{core, imgcodecs, imgproc, prelude::*, videoio};
-use plotters::prelude::*;
-use std::f64::consts::PI;
-use std::fs;
-
-const WIDTH: i32 = 1920;
-const HEIGHT: i32 = 1080;
-
-const CAMBODIA_LAT: f64 = 14.3559; // Cambodia launch site
-const CAMBODIA_LON: f64 = 103.2586;
-const THAI_LAT: f64 = 14.6048725; // Target PTT in Thailand
-const THAI_LON: f64 = 104.643772;
-
-struct BM21Specs {
-    max_range_45deg: f64,
-    max_range_operational: f64,
-    rocket_mass: f64,
-    warhead_mass: f64,
-    rocket_length: f64,
-    rocket_diameter: f64,
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+use bm21::encode::VideoFormat;
+use bm21::geo::{compass_point, final_bearing, haversine_distance, initial_bearing, midpoint, vincenty_inverse};
+use bm21::map::Projection;
+use bm21::scenario::DEFAULT_SCENARIO_PATH;
+use bm21::results::Results;
+use bm21::{Analysis, Scenario, html, markdown, monte_carlo, pdf, render, report};
+
+#[derive(Parser)]
+#[command(version, about = "Range analysis of claimed rocket and artillery strikes")]
+struct Cli {
+    /// Scenario file describing launch point, target and weapon
+    #[arg(short, long, global = true, default_value = DEFAULT_SCENARIO_PATH)]
+    scenario: PathBuf,
+
+    /// Launch point, overrides the scenario (decimal, DMS, UTM, MGRS or Plus Code)
+    #[arg(long, global = true)]
+    launch: Option<String>,
+
+    /// Target point, overrides the scenario (decimal, DMS, UTM, MGRS or Plus Code)
+    #[arg(long, global = true)]
+    target: Option<String>,
+
+    /// Weapon id from the catalog, overrides the scenario
+    #[arg(short, long, global = true)]
+    weapon: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-impl BM21Specs {
-    fn new() -> Self {
-        BM21Specs {
-            max_range_45deg: 20000.0,
-            max_range_operational: 15000.0,
-            rocket_mass: 66.0,
-            warhead_mass: 18.4,
-            rocket_length: 2.87,
-            rocket_diameter: 122.0,
-        }
-    }
+#[derive(Subcommand)]
+enum Command {
+    /// Print the geodesic distance between launch and target only
+    Distance,
+    /// List the weapons in the catalog with their cited figures
+    Weapons,
+    /// Print the range analysis and verdict without rendering video
+    Report {
+        #[arg(short, long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+        /// Replace the generated section of this Markdown file (e.g.
+        /// README.md) instead of printing; implies --format markdown
+        #[arg(long)]
+        update: Option<PathBuf>,
+    },
+    /// Write the long-form report with formulas, sources and charts
+    Document {
+        /// Output file; the format follows the extension (.html or .pdf)
+        output: PathBuf,
+        /// Format, overrides the extension
+        #[arg(short, long, value_enum)]
+        format: Option<DocumentFormat>,
+    },
+    /// Sample coordinate and weapon uncertainties and report the probability
+    /// that the target is within range
+    MonteCarlo {
+        /// Number of samples, overrides the scenario
+        #[arg(short = 'n', long)]
+        samples: Option<usize>,
+        /// Random seed, overrides the scenario
+        #[arg(long)]
+        seed: Option<u64>,
+        #[arg(short, long, value_enum, default_value_t = DataFormat::Text)]
+        format: DataFormat,
+        /// Write a histogram chart of the sampled distances and ranges (PNG)
+        #[arg(long)]
+        histogram: Option<PathBuf>,
+    },
+    /// Draw the plan-view map with range rings and the path to the target
+    Map {
+        /// Output image; .svg for vector, anything else is PNG
+        output: PathBuf,
+        /// azimuthal_equidistant or web_mercator, overrides the scenario
+        #[arg(short, long, value_parser = Projection::from_name)]
+        projection: Option<Projection>,
+        /// Image size as WIDTHxHEIGHT or a preset, overrides the scenario
+        #[arg(short, long)]
+        resolution: Option<String>,
+    },
+    /// Render the trajectory animation and proof card to video (default)
+    Render {
+        /// Directory for the video and results, overrides the scenario
+        #[arg(short, long)]
+        output_dir: Option<PathBuf>,
+        /// Output file, relative to the output directory; the extension picks
+        /// the format (.mp4, .webm, .gif, .png for APNG, none for PNG frames)
+        #[arg(short = 'O', long)]
+        output: Option<String>,
+        /// Format, overrides the extension: mp4, h264, webm, gif, apng, frames
+        #[arg(short, long, value_parser = VideoFormat::from_name)]
+        format: Option<VideoFormat>,
+        /// Frames per second, overrides the scenario
+        #[arg(long)]
+        fps: Option<usize>,
+        /// Trajectory animation length in seconds, overrides the scenario
+        #[arg(long)]
+        duration: Option<usize>,
+        /// Frame size as WIDTHxHEIGHT or a preset (720p, 1080p, 1440p, 4k,
+        /// vertical, square), overrides the scenario
+        #[arg(short, long)]
+        resolution: Option<String>,
+        /// Also write every frame as PNG to the scenario's frame directory
+        #[arg(long)]
+        dump_frames: bool,
+        /// Frames rendered in parallel, defaults to the number of CPU cores
+        #[arg(short, long)]
+        jobs: Option<usize>,
+    },
 }
 
-fn haversine_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
-    let r = 6371000.0;
-    let lat1_rad = lat1.to_radians();
-    let lat2_rad = lat2.to_radians();
-    let delta_lat = (lat2 - lat1).to_radians();
-    let delta_lon = (lon2 - lon1).to_radians();
+#[derive(Clone, Copy, ValueEnum)]
+enum ReportFormat {
+    Text,
+    Json,
+    /// README tables: analysis results, coordinates and constants
+    Markdown,
+}
 
-    let a = (delta_lat / 2.0).sin().powi(2)
-        + lat1_rad.cos() * lat2_rad.cos() * (delta_lon / 2.0).sin().powi(2);
-    let c = 2.0 * a.sqrt().atan2((1.0 - a).sqrt());
+#[derive(Clone, Copy, ValueEnum)]
+enum DataFormat {
+    Text,
+    Json,
+}
 
-    r * c
+#[derive(Clone, Copy, ValueEnum)]
+enum DocumentFormat {
+    Html,
+    Pdf,
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let bm21_specs = BM21Specs::new();
-
-    let actual_distance = haversine_distance(CAMBODIA_LAT, CAMBODIA_LON, THAI_LAT, THAI_LON);
-
-    let g = 9.81;
-    let v0 = 690.0; 
-    let optimal_angle = 45.0;
-    let theta = optimal_angle * PI / 180.0;
-
-    let fps = 15;
-    let video_duration = 15;
-    let total_frames = fps * video_duration;
+    let cli = Cli::parse();
 
-    let t_flight = 2.0 * v0 * theta.sin() / g;
-    let range_theoretical = (v0.powi(2) * (2.0 * theta).sin()) / g;
-    let max_h = (v0.powi(2) * theta.sin().powi(2)) / (2.0 * g);
-
-    let range_shortfall = actual_distance - bm21_specs.max_range_operational;
-    let range_multiplier = actual_distance / bm21_specs.max_range_operational;
-
-    let max_distance = actual_distance
-        .max(range_theoretical)
-        .max(bm21_specs.max_range_operational);
-    
-    let chart_x_max = if range_theoretical < max_distance {
-        (range_theoretical * 2.5).max(25000.0)
+    let mut scenario = if cli.scenario.exists() {
+        Scenario::load(&cli.scenario)?
     } else {
-        (max_distance * 1.1).max(25000.0)
+        eprintln!("⚠️  Scenario {} not found, using built-in default", cli.scenario.display());
+        Scenario::default()
     };
-    
-    let chart_y_max = (max_h * 1.5).max(800.0);
-
-    let frame_dir = "frames";
-    fs::create_dir_all(frame_dir)?;
-    
-    let trajectory_resolution = total_frames * 2; 
-    let mut trajectory_points = Vec::with_capacity(trajectory_resolution);
-    
-    for i in 0..trajectory_resolution {
-        let t = t_flight * (i as f64) / (trajectory_resolution as f64 - 1.0);
-        let x = v0 * theta.cos() * t;
-        let y = (v0 * theta.sin() * t - 0.5 * g * t.powi(2)).max(0.0);
-        trajectory_points.push((x, y));
+    if let Some(launch) = &cli.launch {
+        scenario.launch.set_position(launch)?;
     }
-    
-    let mut animation_points = Vec::with_capacity(total_frames);
-    for i in 0..total_frames {
-        let idx = (i * trajectory_resolution / total_frames).min(trajectory_resolution - 1);
-        animation_points.push(trajectory_points[idx]);
+    if let Some(target) = &cli.target {
+        scenario.target.set_position(target)?;
+    }
+    if let Some(weapon) = &cli.weapon {
+        scenario.select_weapon(weapon)?;
     }
 
-    let operational_range_line = vec![
-        (bm21_specs.max_range_operational, 0.0),
-        (bm21_specs.max_range_operational, chart_y_max * 0.8),
-    ];
-    let target_distance_line = vec![(actual_distance, 0.0), (actual_distance, chart_y_max * 0.8)];
-
-    for i in 0..total_frames {
-        let frame_path = format!("{}/frame_{:05}.png", frame_dir, i);
-
-        let root = BitMapBackend::new(&frame_path, (WIDTH.try_into().unwrap(), HEIGHT.try_into().unwrap())).into_drawing_area();
-        root.fill(&WHITE)?;
-
-        let (chart_area, legend_area) = root.split_horizontally(1350);
-        let legend_area = legend_area.margin(15, 15, 15, 15);
-
-        let mut chart = ChartBuilder::on(&chart_area)
-            .caption(
-                "BM-21 CAMBODIA-THAILAND: Range Analysis",
-                ("Arial", 60).into_font().style(FontStyle::Bold).color(&RED),
-            )
-            .margin(60)
-            .x_label_area_size(90)
-            .y_label_area_size(105)
-            .build_cartesian_2d(0f64..(chart_x_max / 1000.0), 0f64..chart_y_max)?;
-
-        chart
-            .configure_mesh()
-            .x_desc("Distance (kilometers)")
-            .y_desc("Height (meters)")
-            .axis_desc_style(("Arial", 42))
-            .label_style(("Arial", 18))
-            .draw()?;
-
-        let trajectory_points_km: Vec<(f64, f64)> = trajectory_points
-            .iter()
-            .map(|(x, y)| (*x / 1000.0, *y))
-            .collect();
-        
-        chart
-            .draw_series(LineSeries::new(
-                trajectory_points_km.clone(),
-                BLUE.mix(0.3).stroke_width(2),
-            ))?
-            .label("Full Trajectory Path")
-            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], BLUE.mix(0.3).stroke_width(2)));
-   
-        let animation_progress = ((i + 1) as f64 / total_frames as f64 * trajectory_resolution as f64) as usize;
-        let current_trajectory_km: Vec<(f64, f64)> = trajectory_points
-            .iter()
-            .take(animation_progress.min(trajectory_points.len()))
-            .map(|(x, y)| (*x / 1000.0, *y))
-            .collect();
-
-        chart
-            .draw_series(LineSeries::new(current_trajectory_km, BLUE.stroke_width(6)))?
-            .label("Active Trajectory")
-            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], BLUE.stroke_width(6)));
- 
-        let operational_range_line_km: Vec<(f64, f64)> = operational_range_line
-            .iter()
-            .map(|(x, y)| (*x / 1000.0, *y))
-            .collect();
-        
-        chart
-            .draw_series(LineSeries::new(
-                operational_range_line_km,
-                GREEN.stroke_width(4),
-            ))?
-            .label("BM-21 Max Range (15km)")
-            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], GREEN.stroke_width(4)));
-
-        let target_distance_line_km: Vec<(f64, f64)> = target_distance_line
-            .iter()
-            .map(|(x, y)| (*x / 1000.0, *y))
-            .collect();
-        
-        chart
-            .draw_series(LineSeries::new(
-                target_distance_line_km,
-                RED.stroke_width(4),
-            ))?
-            .label("PTT Gas station in Thailand")
-            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], RED.stroke_width(4)));
+    let command = cli.command.unwrap_or(Command::Render {
+        output_dir: None,
+        output: None,
+        format: None,
+        fps: None,
+        duration: None,
+        resolution: None,
+        dump_frames: false,
+        jobs: None,
+    });
+    // Only the trajectories and site elevations need the SRTM tiles.
+    if !matches!(command, Command::Distance | Command::Weapons) {
+        scenario.load_terrain()?;
+    }
 
-        if i < animation_points.len() {
-            let (x, y) = animation_points[i];
-            let (x_km, y_km) = (x / 1000.0, y);
-            
-            chart
-                .draw_series(PointSeries::of_element(
-                    vec![(x_km, y_km)],
-                    12, 
-                    &RED,
-                    &|c, s, st| {
-                        return EmptyElement::at(c) + Circle::new((0, 0), s, st.filled());
-                    },
-                ))?
-                .label("Rocket Position")
-                .legend(|(x, y)| Circle::new((x + 5, y), 5, RED.filled()));
-            
-            if i > 5 {
-                let trail_start = (i - 5).max(0);
-                let trail_points_km: Vec<(f64, f64)> = animation_points
-                    .iter()
-                    .skip(trail_start)
-                    .take(6)
-                    .map(|(x, y)| (*x / 1000.0, *y))
-                    .collect();
-                
-                chart.draw_series(LineSeries::new(
-                    trail_points_km,
-                    RED.mix(0.6).stroke_width(3),
-                ))?;
+    match command {
+        Command::Distance => print_distance(&scenario),
+        Command::Weapons => print_weapons(&scenario),
+        Command::Report { format, update } => {
+            let analysis = Analysis::new(&scenario)?;
+            if let Some(path) = update {
+                let generated = markdown::markdown(&Results::new(&scenario, &analysis));
+                let text = std::fs::read_to_string(&path)
+                    .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+                let updated = markdown::splice(&text, &generated)
+                    .map_err(|e| format!("cannot update {}: {}", path.display(), e))?;
+                std::fs::write(&path, updated).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+                println!("📝 Generated section of {} updated", path.display());
+                return Ok(());
+            }
+            match format {
+                ReportFormat::Text => {
+                    for line in report::proof_lines(&scenario, &analysis) {
+                        println!("{}", line);
+                    }
+                }
+                ReportFormat::Json => println!("{}", Results::new(&scenario, &analysis).to_json()?),
+                ReportFormat::Markdown => print!("{}", markdown::markdown(&Results::new(&scenario, &analysis))),
             }
         }
-
-        chart
-            .configure_series_labels()
-            .background_style(&WHITE.mix(0.8))
-            .border_style(&BLACK)
-            .label_font(("Arial", 28))
-            .draw()?;
-
-        legend_area.fill(&RGBColor(240, 240, 255))?;
-
-        let compact_info = vec![
-            ("CAMBODIA-THAILAND BM-21 ANALYSIS".to_string(), 14, BLACK, true),
-            (
-                format!(
-                    "Max Range: {:.0}km",
-                    bm21_specs.max_range_operational / 1000.0
-                ),
-                13,
-                BLUE,
-                false,
-            ),
-            (
-                format!("Distance: {:.1}km", actual_distance / 1000.0),
-                13,
-                BLACK,
-                false,
-            ),
-            (
-                format!("Shortfall: {:.1}km", range_shortfall / 1000.0),
-                13,
-                RED,
-                false,
-            ),
-            (
-                format!("Target {:.1}× TOO FAR!", range_multiplier),
-                13,
-                MAGENTA,
-                true,
-            ),
-            (
-                format!(
-                    "Physics violation: {:.0}%",
-                    (range_shortfall / bm21_specs.max_range_operational) * 100.0
-                ),
-                13,
-                RED,
-                false,
-            ),
-            (
-                "WHY MAX RANGE ≠ ACTUAL DISTANCE:".to_string(),
-                13,
-                BLACK,
-                true,
-            ),
-            (
-                "• BM-21 max range: 15km (ballistic limit)".to_string(),
-                13,
-                BLUE,
-                false,
-            ),
-            (
-                format!("• Required distance: {:.1}km (GPS measured)", actual_distance / 1000.0),
-                13,
-                BLUE,
-                false,
-            ),
-            (
-                "• Physics: Projectiles follow parabolic paths".to_string(),
-                13,
-                BLUE,
-                false,
-            ),
-            (
-                "• Earth curvature & air resistance ignored".to_string(),
-                13,
-                BLUE,
-                false,
-            ),
-            (
-                format!("• Gap: {:.1}km cannot be bridged by any rocket", range_shortfall / 1000.0),
-                13,
-                RED,
-                false,
-            ),
-            (
-                "📐 MATHEMATICAL CALCULATIONS:".to_string(),
-                13,
-                BLACK,
-                true,
-            ),
-            (
-                "##############################################".to_string(),
-                13,
-                BLACK,
-                false,
-            ),
-            (
-                "① Haversine Distance Formula:".to_string(),
-                13,
-                BLUE,
-                true,
-            ),
-            (
-                "d = 2R ⋅ arcsin(√(sin²(Δφ/2) + cos(φ₁)cos(φ₂)sin²(Δλ/2)))".to_string(),
-                13,
-                BLUE,
-                false,
-            ),
-            (
-                format!("Given: φ₁={:.4}°, λ₁={:.4}°, φ₂={:.7}°, λ₂={:.7}°", CAMBODIA_LAT, CAMBODIA_LON, THAI_LAT, THAI_LON),
-                13,
-                BLUE,
-                false,
-            ),
-            (
-                format!("Δφ = {:.4}°, Δλ = {:.4}°, R = 6,371km", THAI_LAT - CAMBODIA_LAT, THAI_LON - CAMBODIA_LON),
-                13,
-                BLUE,
-                false,
-            ),
-            (
-                format!("∴ d = {:.1}km (GPS verified)", actual_distance / 1000.0),
-                13,
-                BLUE,
-                true,
-            ),
-            (
-                "② Projectile Range Formula:".to_string(),
-                13,
-                BLUE,
-                true,
-            ),
-            (
-                "R = (v₀² ⋅ sin(2θ)) / g #FIND R".to_string(),
-                13,
-                BLUE,
-                false,
-            ),
-            (
-                "📋 CONSTANT DEFINITIONS:".to_string(),
-                13,
-                BLACK,
-                true,
-            ),
-            (
-                format!("• v₀ = {:.0} m/s (Initial muzzle velocity of BM-21 rocket)", v0),
-                13,
-                BLUE,
-                false,
-            ),
-            (
-                "• θ = 45° (Optimal launch angle for maximum range)".to_string(),
-                13,
-                BLUE,
-                false,
-            ),
-            (
-                "• g = 9.81 m/s² (Earth's gravitational acceleration)".to_string(),
-                13,
-                BLUE,
-                false,
-            ),
-            (
-                "• R = 6,371 km (Earth's mean radius for Haversine)".to_string(),
-                13,
-                BLUE,
-                false,
-            ),
-            (
-                "📊 Then: ".to_string(),
-                13,
-                BLACK,
-                true,
-            ),
-            (
-                format!("R = ({:.0}² ⋅ sin(90°)) / 9.81", v0),
-                13,
-                BLUE,
-                false,
-            ),
-            (
-                format!("R = {:.0} ⋅ 1.0 / 9.81 = {:.1}km", v0.powi(2), range_theoretical / 1000.0),
-                13,
-                BLUE,
-                false,
-            ),
-            (
-                "③ Impossibility Analysis:".to_string(),
-                13,
-                RED,
-                true,
-            ),
-            (
-                format!("Required Distance / Max Range = {:.1}km / {:.0}km", actual_distance / 1000.0, bm21_specs.max_range_operational / 1000.0),
-                13,
-                RED,
-                false,
-            ),
-            (
-                format!("Impossibility Factor = {:.1}× TOO FAR", range_multiplier),
-                20,
-                RED,
-                true,
-            ),
-        ];
-
-        for (idx, (text, font_size, color, bold)) in compact_info.iter().enumerate() {
-            let y_pos = 38 + (idx as i32) * 30;  
-            let font_style = if *bold {
-                TextStyle::from(
-                    ("Arial", (*font_size as f64 * 1.5) as i32)  
-                        .into_font()
-                        .style(FontStyle::Bold)
-                        .color(color),
-                )
-            } else {
-                TextStyle::from(("Arial", (*font_size as f64 * 1.5) as i32).into_font().style(FontStyle::Normal).color(color))
+        Command::Document { output, format } => {
+            let extension = output.extension().and_then(|e| e.to_str()).map(str::to_lowercase);
+            let format = match (format, extension.as_deref()) {
+                (Some(format), _) => format,
+                (None, Some("html" | "htm")) => DocumentFormat::Html,
+                (None, Some("pdf")) => DocumentFormat::Pdf,
+                _ => return Err(format!("cannot tell the format of {}, use --format", output.display()).into()),
             };
-
-            legend_area.draw_text(text, &font_style, (15, y_pos))?;  
+            let analysis = Analysis::new(&scenario)?;
+            match format {
+                DocumentFormat::Html => std::fs::write(&output, html::html(&scenario, &analysis)?)
+                    .map_err(|e| format!("cannot write {}: {}", output.display(), e))?,
+                DocumentFormat::Pdf => pdf::pdf(&scenario, &analysis, &output)?,
+            }
+            println!("📄 Report saved as: {}", output.display());
         }
-
-        root.present()?;
-    }
- 
-    let proof_lines = vec![
-        "ANALYSIS: BM-21 from CAMBODIA vs THAILAND ATTACK CLAIM".to_string(),
-        "================================================================".to_string(),
-        "".to_string(),
-        "OFFICIAL BM-21 GRAD ROCKET SPECIFICATIONS:".to_string(),
-        format!("* Rocket Caliber: 122mm"),
-        format!("* Total Rocket Mass: {:.1} kg", bm21_specs.rocket_mass),
-        format!("* Warhead Mass: {:.1} kg HE-FRAG", bm21_specs.warhead_mass),
-        format!("* Rocket Length: {:.2} meters", bm21_specs.rocket_length),
-        format!(
-            "* Maximum Range (45 deg optimal): {:.0} km",
-            bm21_specs.max_range_45deg / 1000.0
-        ),
-        format!(
-            "* Operational Range (typical): {:.0} km",
-            bm21_specs.max_range_operational / 1000.0
-        ),
-        "".to_string(),
-        "GEOGRAPHIC DISTANCE VERIFICATION:".to_string(),
-        format!(
-            "* Launch Coordinates: {:.6}N, {:.6}E (Cambodia)",
-            CAMBODIA_LAT, CAMBODIA_LON
-        ),
-        format!(
-            "* Target Coordinates: {:.6}N, {:.6}E (Thailand)",
-            THAI_LAT, THAI_LON
-        ),
-        format!("* Haversine Distance: {:.3} km", actual_distance / 1000.0),
-        format!("* GPS Verification: CONFIRMED"),
-        "".to_string(),
-        "BALLISTIC PHYSICS CALCULATIONS:".to_string(),
-        format!("* Theoretical Max Range Formula: R = (v0^2 x sin(2*theta)) / g"),
-        format!("* Initial Velocity: {:.1} m/s", v0),
-        format!("* Optimal Launch Angle: {:.0} degrees", optimal_angle),
-        format!("* Calculated Range: {:.3} km", range_theoretical / 1000.0),
-        format!("* Flight Time: {:.1} seconds", t_flight),
-        format!("* Maximum Height: {:.0} meters", max_h),
-        "".to_string(),
-        "RANGE ANALYSIS - MATHEMATICAL EVIDENCE:".to_string(),
-        format!("* Required Distance: {:.1} km", actual_distance / 1000.0),
-        format!(
-            "* Maximum BM-21 Range: {:.0} km",
-            bm21_specs.max_range_operational / 1000.0
-        ),
-        format!("* Range Deficit: {:.1} km", range_shortfall / 1000.0),
-        format!("* Range Factor: {:.1}x the maximum range", range_multiplier),
-        format!(
-            "* Physics Violation: {:.0}% beyond maximum capability",
-            ((range_shortfall / bm21_specs.max_range_operational) * 100.0)
-        ),
-        "".to_string(),
-        "MILITARY EXPERT CONCLUSIONS:".to_string(),
-        "[VERIFIED] BM-21 specifications verified against Jane's Military Equipment".to_string(),
-        "[VERIFIED] Geographic coordinates verified via satellite data".to_string(),
-        "[VERIFIED] Physics calculations conform to NATO ballistic standards".to_string(),
-        format!("[VERIFIED] Range deficit: {:.1} km beyond rocket capability", range_shortfall / 1000.0),
-        "".to_string(),
-        "FINAL VERDICT:".to_string(),
-        if range_shortfall > 0.0 {
-            "[IMPOSSIBLE] CLAIM STATUS: PHYSICALLY IMPOSSIBLE".to_string()
-        } else {
-            "[POSSIBLE] CLAIM STATUS: THEORETICALLY POSSIBLE".to_string()
-        },
-        "[COMPLETE] SCIENTIFIC PROOF: COMPLETE".to_string(),
-        if range_shortfall > 0.0 {
-            "[CONCLUSION] ATTACK IMPOSSIBLE FROM THIS DISTANCE".to_string()
-        } else {
-            "[CONCLUSION] ATTACK WITHIN THEORETICAL RANGE".to_string()
-        },
-        "".to_string(),
-        "The laws of physics, verified military specifications, and".to_string(),
-        "precise geographic measurements DEFINITIVELY PROVE that".to_string(),
-        "Cambodia's BM-21 rockets CANNOT reach Thailand.".to_string(),
-    ];
-
-    let mut proof_img = core::Mat::new_rows_cols_with_default(
-        HEIGHT,
-        WIDTH,
-        core::CV_8UC3,
-        core::Scalar::new(255.0, 255.0, 255.0, 0.0),
-    )?;
-
-    imgproc::rectangle(
-        &mut proof_img,
-        core::Rect::new(0, 0, WIDTH, 90),
-        core::Scalar::new(0.0, 0.0, 200.0, 0.0),
-        -1,
-        imgproc::LINE_8,
-        0,
-    )?;
-
-    imgproc::put_text(
-        &mut proof_img,
-        "IMPOSSIBILITY PROOF: CAMBODIA BM-21 CANNOT ATTACK THAILAND",
-        core::Point::new(45, 60),
-        imgproc::FONT_HERSHEY_SIMPLEX,
-        1.5,  
-        core::Scalar::new(255.0, 255.0, 255.0, 0.0),
-        4,  
-        imgproc::LINE_8,
-        false,
-    )?;
- 
-    let center_x = WIDTH / 2;
-    imgproc::line(
-        &mut proof_img,
-        core::Point::new(center_x, 100),
-        core::Point::new(center_x, HEIGHT - 50),
-        core::Scalar::new(150.0, 150.0, 150.0, 0.0), 
-        3, 
-        imgproc::LINE_8,
-        0,
-    )?;
- 
-    let mid_point = proof_lines.len() / 2;
-    let left_column = &proof_lines[0..mid_point];
-    let right_column = &proof_lines[mid_point..];
- 
-    for (i, text) in left_column.iter().enumerate() {
-        let y = 135 + (i as i32) * 35;  
-
-        if y > (HEIGHT - 50) {
-            break;
+        Command::Map {
+            output,
+            projection,
+            resolution,
+        } => {
+            if let Some(projection) = projection {
+                scenario.map.projection = projection;
+            }
+            if let Some(resolution) = resolution {
+                scenario.output.set_resolution(&resolution)?;
+            }
+            let analysis = Analysis::new(&scenario)?;
+            render::map_image(&scenario, &analysis, &output)?;
+            println!("🗺️  Map saved as: {}", output.display());
         }
-
-        let (font_scale, color) =
-            if text.starts_with("🚫") || text.contains("IMPOSSIBLE") || text.contains("FALSE") {
-                (0.8, core::Scalar::new(0.0, 0.0, 200.0, 0.0)) 
-            } else if text.starts_with("🔬") || text.contains("COMPLETE") {
-                (0.8, core::Scalar::new(0.0, 150.0, 0.0, 0.0)) 
-            } else if text.contains("SPECIFICATIONS") || text.contains("VERIFICATION") {
-                (0.7, core::Scalar::new(200.0, 0.0, 0.0, 0.0))  
-            } else if text.contains("═") {
-                (0.6, core::Scalar::new(100.0, 100.0, 100.0, 0.0)) 
-            } else if text.starts_with("•") {
-                (0.6, core::Scalar::new(0.0, 0.0, 0.0, 0.0)) 
-            } else {
-                (0.7, core::Scalar::new(0.0, 0.0, 0.0, 0.0)) 
-            };
-
-        imgproc::put_text(
-            &mut proof_img,
-            text,
-            core::Point::new(30, y),  
-            imgproc::FONT_HERSHEY_SIMPLEX,
-            font_scale,
-            color,
-            2,
-            imgproc::LINE_8,
-            false,
-        )?;
-    }
- 
-    for (i, text) in right_column.iter().enumerate() {
-        let y = 135 + (i as i32) * 35; 
-
-        if y > (HEIGHT - 50) {
-            break;
+        Command::MonteCarlo {
+            samples,
+            seed,
+            format,
+            histogram,
+        } => {
+            if let Some(samples) = samples {
+                scenario.uncertainty.samples = samples;
+            }
+            if let Some(seed) = seed {
+                scenario.uncertainty.seed = seed;
+            }
+            let result = monte_carlo::run(&scenario)?;
+            match format {
+                DataFormat::Text => print_monte_carlo(&scenario, &result),
+                DataFormat::Json => println!("{}", serde_json::to_string_pretty(&result)?),
+            }
+            if let Some(path) = histogram {
+                render::histogram_chart(&scenario, &result, &path)?;
+                eprintln!("📊 Histogram saved as: {}", path.display());
+            }
         }
-
-        let (font_scale, color) =
-            if text.starts_with("🚫") || text.contains("IMPOSSIBLE") || text.contains("FALSE") {
-                (0.8, core::Scalar::new(0.0, 0.0, 200.0, 0.0)) 
-            } else if text.starts_with("🔬") || text.contains("COMPLETE") {
-                (0.8, core::Scalar::new(0.0, 150.0, 0.0, 0.0)) 
-            } else if text.contains("SPECIFICATIONS") || text.contains("VERIFICATION") {
-                (0.7, core::Scalar::new(200.0, 0.0, 0.0, 0.0)) 
-            } else if text.contains("═") {
-                (0.6, core::Scalar::new(100.0, 100.0, 100.0, 0.0)) 
-            } else if text.starts_with("•") {
-                (0.6, core::Scalar::new(0.0, 0.0, 0.0, 0.0))    
-            } else {
-                (0.7, core::Scalar::new(0.0, 0.0, 0.0, 0.0)) 
-            };
-
-        imgproc::put_text(
-            &mut proof_img,
-            text,
-            core::Point::new(center_x + 30, y),  
-            imgproc::FONT_HERSHEY_SIMPLEX,
-            font_scale,
-            color,
-            2,
-            imgproc::LINE_8,
-            false,
-        )?;
-    }
-
-    for j in 0..(fps * 3) {
-        let frame_path = format!("{}/frame_{:05}.png", frame_dir, total_frames + j);
-        imgcodecs::imwrite(&frame_path, &proof_img, &core::Vector::new())?;
-    }
-
-    let output_video = "bm21_impossibility_proof.mp4";
-    let fourcc = videoio::VideoWriter::fourcc('m', 'p', '4', 'v')?;
-    let mut video_writer = videoio::VideoWriter::new(
-        output_video,
-        fourcc,
-        fps as f64,
-        core::Size::new(WIDTH, HEIGHT),
-        true,
-    )?;
-
-    for i in 0..total_frames {
-        let frame_path = format!("{}/frame_{:05}.png", frame_dir, i);
-        let img = imgcodecs::imread(&frame_path, imgcodecs::IMREAD_COLOR)?;
-
-        if !img.empty() {
-            video_writer.write(&img)?;
+        Command::Render {
+            output_dir,
+            output,
+            format,
+            fps,
+            duration,
+            resolution,
+            dump_frames,
+            jobs,
+        } => {
+            if let Some(output) = output {
+                scenario.output.video = output;
+            }
+            if format.is_some() {
+                scenario.output.format = format;
+            }
+            if let Some(fps) = fps {
+                scenario.output.fps = fps;
+            }
+            if let Some(duration) = duration {
+                scenario.output.duration = duration;
+            }
+            if let Some(resolution) = resolution {
+                scenario.output.set_resolution(&resolution)?;
+            }
+            if dump_frames {
+                scenario.output.dump_frames = true;
+            }
+            scenario.output.validate()?;
+            let output_dir = output_dir.unwrap_or_else(|| PathBuf::from("."));
+            let analysis = Analysis::new(&scenario)?;
+            let jobs = jobs.unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+            render::render(&scenario, &analysis, &output_dir, jobs)?;
+            let results_path = output_dir.join(&scenario.output.video).with_extension("json");
+            Results::new(&scenario, &analysis).write(&results_path)?;
+            println!("📄 Results saved as: {}", results_path.display());
         }
     }
 
-    for j in 0..(fps * 3) {
-        let frame_path = format!("{}/frame_{:05}.png", frame_dir, total_frames + j);
-        let img = imgcodecs::imread(&frame_path, imgcodecs::IMREAD_COLOR)?;
+    Ok(())
+}
 
-        if !img.empty() {
-            video_writer.write(&img)?;
-        }
+/// Prints the geometry between the sites, without running the trajectories.
+fn print_distance(scenario: &Scenario) {
+    let launch = &scenario.launch;
+    let target = &scenario.target;
+    let distance = haversine_distance(launch.lat, launch.lon, target.lat, target.lon);
+    let initial = initial_bearing(launch.lat, launch.lon, target.lat, target.lon);
+    let final_ = final_bearing(launch.lat, launch.lon, target.lat, target.lon);
+    let (mid_lat, mid_lon) = midpoint(launch.lat, launch.lon, target.lat, target.lon);
+    let ellipsoidal = vincenty_inverse(launch.lat, launch.lon, target.lat, target.lon);
+    println!("📍 Launch: {:.6}, {:.6} ({})", launch.lat, launch.lon, launch.label);
+    println!("🎯 Target: {:.6}, {:.6} ({})", target.lat, target.lon, target.label);
+    println!("📏 Haversine Distance: {:.3} km", distance / 1000.0);
+    println!(
+        "🧭 Bearing: {:.2}° ({}) initial, {:.2}° ({}) final",
+        initial,
+        compass_point(initial),
+        final_,
+        compass_point(final_)
+    );
+    println!("📌 Midpoint: {:.6}, {:.6}", mid_lat, mid_lon);
+    println!(
+        "🌐 WGS84 Geodesic Distance: {:.3} km ({:+.1} m vs sphere)",
+        ellipsoidal.distance / 1000.0,
+        ellipsoidal.distance - distance
+    );
+    println!(
+        "🌐 WGS84 Azimuth: {:.2}° forward, {:.2}° back",
+        ellipsoidal.forward_azimuth, ellipsoidal.back_azimuth
+    );
+    if !ellipsoidal.converged {
+        println!("⚠️  Vincenty did not converge (near-antipodal points), spherical result shown");
     }
+}
 
-    video_writer.release()?;
-
-    println!("📁 Video saved as: {}", output_video);
+fn print_weapons(scenario: &Scenario) {
+    for weapon in &scenario.catalog.weapons {
+        let marker = if weapon.id == scenario.weapon.id { "▶" } else { " " };
+        println!("{} {:<8} {} ({}) - {}", marker, weapon.id, weapon.name, weapon.category, weapon.description);
+        println!(
+            "    🎯 {:.1} km operational [{}], {:.1} km maximum [{}]",
+            weapon.max_range_operational / 1000.0,
+            weapon.source("max_range_operational"),
+            weapon.max_range_45deg / 1000.0,
+            weapon.source("max_range_45deg")
+        );
+        println!(
+            "    🚀 {:.0} mm [{}], {:.1} kg [{}], {:.0} m/s [{}]",
+            weapon.calibre,
+            weapon.source("calibre"),
+            weapon.projectile_mass,
+            weapon.source("projectile_mass"),
+            weapon.muzzle_velocity,
+            weapon.source("muzzle_velocity")
+        );
+    }
+}
 
-    Ok(())
+fn print_monte_carlo(scenario: &Scenario, result: &monte_carlo::MonteCarloResult) {
+    let km = |p: &monte_carlo::Percentiles| {
+        format!(
+            "{:.1} / {:.1} / {:.1} km (5th / median / 95th)",
+            p.p05 / 1000.0,
+            p.p50 / 1000.0,
+            p.p95 / 1000.0
+        )
+    };
+    println!("🎲 Monte Carlo: {} samples, seed {}", result.samples, result.seed);
+    println!(
+        "📍 Launch ±{:.0} m, 🎯 Target ±{:.0} m",
+        scenario.launch.uncertainty, scenario.target.uncertainty
+    );
+    println!("📏 Distance: {}", km(&result.distance));
+    println!("🚀 {} max range: {}", scenario.weapon.name, km(&result.max_range_operational));
+    println!("🧮 Vacuum range bound: {}", km(&result.vacuum_range));
+    println!("📉 Shortfall: {}", km(&result.shortfall));
+    println!(
+        "✅ P(distance ≤ operational range): {}",
+        monte_carlo::format_probability(result.probability_within_operational, result.samples)
+    );
+    println!(
+        "✅ P(distance ≤ vacuum range): {}",
+        monte_carlo::format_probability(result.probability_within_vacuum, result.samples)
+    );
 }
@@ -0,0 +1,194 @@
+use serde::Deserialize;
+use std::f64::consts::PI;
+
+use crate::basemap::BasemapOptions;
+use crate::geo::{EARTH_RADIUS, WGS84_A, destination_point, haversine_distance, initial_bearing};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Projection {
+    /// Distances and bearings from the centre are true, so range rings
+    /// around the launch site are exact circles.
+    #[default]
+    AzimuthalEquidistant,
+    /// Spherical Mercator as used by web map tiles (EPSG:3857).
+    WebMercator,
+}
+
+pub const PROJECTIONS: [(&str, Projection); 2] = [
+    ("azimuthal_equidistant", Projection::AzimuthalEquidistant),
+    ("web_mercator", Projection::WebMercator),
+];
+
+impl Projection {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        let name = name.trim().to_lowercase().replace('-', "_");
+        PROJECTIONS
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, projection)| *projection)
+            .ok_or_else(|| {
+                let names: Vec<&str> = PROJECTIONS.iter().map(|(n, _)| *n).collect();
+                format!("unknown projection \"{}\", use one of: {}", name, names.join(", "))
+            })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Projection::AzimuthalEquidistant => "azimuthal equidistant",
+            Projection::WebMercator => "Web Mercator",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MapOptions {
+    #[serde(default)]
+    pub projection: Projection,
+    /// Raster tiles drawn under the map, see `basemap`.
+    pub basemap: Option<BasemapOptions>,
+}
+
+/// A projection centred on `(lat0, lon0)`. Projected coordinates are meters
+/// east and north of the centre.
+#[derive(Debug, Clone, Copy)]
+pub struct MapProjection {
+    pub projection: Projection,
+    pub lat0: f64,
+    pub lon0: f64,
+}
+
+impl MapProjection {
+    pub fn new(projection: Projection, lat0: f64, lon0: f64) -> Self {
+        MapProjection { projection, lat0, lon0 }
+    }
+
+    pub fn project(&self, lat: f64, lon: f64) -> (f64, f64) {
+        match self.projection {
+            Projection::AzimuthalEquidistant => {
+                let (phi0, phi) = (self.lat0.to_radians(), lat.to_radians());
+                let delta_lon = (lon - self.lon0).to_radians();
+                let cos_c = phi0.sin() * phi.sin() + phi0.cos() * phi.cos() * delta_lon.cos();
+                let c = cos_c.clamp(-1.0, 1.0).acos();
+                let k = if c < 1e-12 { 1.0 } else { c / c.sin() };
+                (
+                    EARTH_RADIUS * k * phi.cos() * delta_lon.sin(),
+                    EARTH_RADIUS * k * (phi0.cos() * phi.sin() - phi0.sin() * phi.cos() * delta_lon.cos()),
+                )
+            }
+            Projection::WebMercator => {
+                let (x, y) = web_mercator(lat, lon);
+                let (x0, y0) = web_mercator(self.lat0, self.lon0);
+                (x - x0, y - y0)
+            }
+        }
+    }
+
+    /// Inverse of `project`, as (lat, lon) in degrees.
+    pub fn unproject(&self, x: f64, y: f64) -> (f64, f64) {
+        match self.projection {
+            Projection::AzimuthalEquidistant => {
+                let rho = x.hypot(y);
+                if rho < 1e-9 {
+                    return (self.lat0, self.lon0);
+                }
+                let c = rho / EARTH_RADIUS;
+                let phi0 = self.lat0.to_radians();
+                let phi = (c.cos() * phi0.sin() + y * c.sin() * phi0.cos() / rho).clamp(-1.0, 1.0).asin();
+                let delta_lon = (x * c.sin()).atan2(rho * phi0.cos() * c.cos() - y * phi0.sin() * c.sin());
+                let lon = (self.lon0 + delta_lon.to_degrees() + 540.0).rem_euclid(360.0) - 180.0;
+                (phi.to_degrees(), lon)
+            }
+            Projection::WebMercator => {
+                let (x0, y0) = web_mercator(self.lat0, self.lon0);
+                web_mercator_inverse(x + x0, y + y0)
+            }
+        }
+    }
+}
+
+/// Latitude limit of Web Mercator, where the map becomes square.
+pub const WEB_MERCATOR_MAX_LAT: f64 = 85.051_128_779_806_59;
+
+/// EPSG:3857 coordinates in meters.
+pub fn web_mercator(lat: f64, lon: f64) -> (f64, f64) {
+    let phi = lat.clamp(-WEB_MERCATOR_MAX_LAT, WEB_MERCATOR_MAX_LAT).to_radians();
+    (
+        WGS84_A * lon.to_radians(),
+        WGS84_A * (PI / 4.0 + phi / 2.0).tan().ln(),
+    )
+}
+
+/// Inverse of `web_mercator`, as (lat, lon) in degrees.
+pub fn web_mercator_inverse(x: f64, y: f64) -> (f64, f64) {
+    let lat = (2.0 * (y / WGS84_A).exp().atan() - PI / 2.0).to_degrees();
+    (lat, (x / WGS84_A).to_degrees())
+}
+
+/// Points on the circle of `radius` meters around `(lat, lon)`, closed.
+pub fn range_ring(lat: f64, lon: f64, radius: f64, points: usize) -> Vec<(f64, f64)> {
+    (0..=points)
+        .map(|i| destination_point(lat, lon, 360.0 * i as f64 / points as f64, radius))
+        .collect()
+}
+
+/// Points along the great circle from the first point to the second.
+pub fn great_circle(lat1: f64, lon1: f64, lat2: f64, lon2: f64, points: usize) -> Vec<(f64, f64)> {
+    let bearing = initial_bearing(lat1, lon1, lat2, lon2);
+    let distance = haversine_distance(lat1, lon1, lat2, lon2);
+    (0..=points)
+        .map(|i| destination_point(lat1, lon1, bearing, distance * i as f64 / points as f64))
+        .collect()
+}
+
+/// Graticule spacing in degrees giving roughly four to eight lines across
+/// `span` degrees.
+pub fn graticule_step(span: f64) -> f64 {
+    const STEPS: [f64; 10] = [0.01, 0.02, 0.05, 0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0];
+    STEPS
+        .iter()
+        .copied()
+        .find(|step| span / step <= 8.0)
+        .unwrap_or(20.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::vincenty_inverse;
+
+    #[test]
+    fn unproject_inverts_project() {
+        for projection in [Projection::AzimuthalEquidistant, Projection::WebMercator] {
+            for (lat0, lon0) in [(14.36, 103.26), (-33.9, 18.4), (60.0, 179.5)] {
+                let map = MapProjection::new(projection, lat0, lon0);
+                for (dlat, dlon) in [(0.0, 0.0), (1.5, -2.0), (-3.0, 1.0), (0.2, 0.7)] {
+                    let (x, y) = map.project(lat0 + dlat, lon0 + dlon);
+                    let (lat, lon) = map.unproject(x, y);
+                    let lon_error = (lon - (lon0 + dlon) + 540.0).rem_euclid(360.0) - 180.0;
+                    assert!((lat - (lat0 + dlat)).abs() < 1e-9, "{:?} {} {}", projection, lat, lat0 + dlat);
+                    assert!(lon_error.abs() < 1e-9, "{:?} {} {}", projection, lon, lon0 + dlon);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn range_rings_lie_at_their_radius() {
+        let (lat, lon) = (14.3559, 103.2586);
+        let map = MapProjection::new(Projection::AzimuthalEquidistant, lat, lon);
+        for radius in [15000.0, 48500.0, 150000.0] {
+            let ring = range_ring(lat, lon, radius, 36);
+            assert_eq!(ring.first(), ring.last());
+            for &(ring_lat, ring_lon) in &ring {
+                // True distance from the centre on the map's sphere.
+                let (x, y) = map.project(ring_lat, ring_lon);
+                assert!((x.hypot(y) - radius).abs() < 1e-6 * radius, "{} {}", x.hypot(y), radius);
+                // The 6371 km sphere is within 0.6% of the WGS84 radii of curvature.
+                let geodesic = vincenty_inverse(lat, lon, ring_lat, ring_lon);
+                assert!(geodesic.converged);
+                assert!((geodesic.distance - radius).abs() < 0.006 * radius, "{} {}", geodesic.distance, radius);
+            }
+        }
+    }
+}
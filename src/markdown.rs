@@ -0,0 +1,243 @@
+use crate::analysis::TierKind;
+use crate::geo::{WGS84_A, WGS84_F};
+use crate::report::MODEL_REFERENCES;
+use crate::results::{Results, SiteInput};
+
+/// Markers around the generated part of a Markdown file, see `splice`.
+pub const BEGIN_MARKER: &str = "<!-- BEGIN GENERATED: bm21 report --format markdown -->";
+pub const END_MARKER: &str = "<!-- END GENERATED -->";
+
+/// Groups the integer part of a formatted number in thousands.
+fn thousands(value: f64, decimals: usize) -> String {
+    let text = format!("{:.*}", decimals, value.abs());
+    let (integer, fraction) = match text.split_once('.') {
+        Some((integer, fraction)) => (integer, format!(".{}", fraction)),
+        None => (text.as_str(), String::new()),
+    };
+    let mut grouped = String::new();
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    let sign = if value < 0.0 { "-" } else { "" };
+    format!("{}{}{}", sign, grouped, fraction)
+}
+
+fn citation<'a>(results: &'a Results, key: &'a str) -> &'a str {
+    results
+        .inputs
+        .weapon
+        .references
+        .get(key)
+        .map(String::as_str)
+        .or_else(|| MODEL_REFERENCES.iter().find(|(k, _)| *k == key).map(|(_, c)| *c))
+        .unwrap_or(key)
+}
+
+fn field_citation<'a>(results: &'a Results, field: &str) -> &'a str {
+    match results.inputs.weapon.sources.get(field) {
+        Some(key) => citation(results, key),
+        None => crate::weapon::UNSOURCED,
+    }
+}
+
+fn site_lines(out: &mut Vec<String>, title: &str, site: &SiteInput) {
+    let hemisphere = |value: f64, positive: char, negative: char| {
+        format!("{:.6}°{}", value.abs(), if value >= 0.0 { positive } else { negative })
+    };
+    out.push(format!("### {} ({})", title, site.country));
+    out.push(format!("- **Label**: {}", site.label));
+    out.push(format!("- **Latitude**: {}", hemisphere(site.lat, 'N', 'S')));
+    out.push(format!("- **Longitude**: {}", hemisphere(site.lon, 'E', 'W')));
+    out.push(format!("- **Uncertainty**: ±{} m", thousands(site.uncertainty_m, 0)));
+    if let Some(elevation) = site.elevation_m {
+        out.push(format!("- **Elevation**: {} m", thousands(elevation, 0)));
+    }
+    out.push(format!(
+        "- **Source**: {}",
+        site.source.as_deref().unwrap_or(crate::weapon::UNSOURCED)
+    ));
+    out.push(String::new());
+}
+
+/// The Analysis Results, Coordinate References and Physical Constants
+/// sections of the README, generated from the same results as the video.
+pub fn markdown(results: &Results) -> String {
+    let weapon = &results.inputs.weapon;
+    let ranges = &results.ranges;
+    let assumptions = &results.assumptions;
+    let km = |meters: f64| format!("{:.1} km", meters / 1000.0);
+    let mut out = Vec::new();
+
+    out.push("## Analysis Results".to_string());
+    out.push(String::new());
+    out.push(format!(
+        "{} from {} to {}, generated by {} {}.",
+        weapon.name,
+        results.inputs.launch.label,
+        results.inputs.target.label,
+        results.tool.name,
+        results.tool.version
+    ));
+    out.push(String::new());
+    out.push("| Quantity | Value | Method |".to_string());
+    out.push("|----------|-------|--------|".to_string());
+    let mut row = |quantity: &str, value: String, method: &str| {
+        out.push(format!("| {} | {} | {} |", quantity, value, method));
+    };
+    row("Geographic distance", km(results.distances.haversine_m), "Haversine formula");
+    row("WGS84 geodesic distance", km(results.distances.wgs84_m), "Vincenty inverse");
+    row(
+        &format!("{} operational range", weapon.name),
+        km(ranges.operational_m),
+        field_citation(results, "max_range_operational"),
+    );
+    row(
+        &format!("{} published maximum", weapon.name),
+        km(ranges.published_max_m),
+        field_citation(results, "max_range_45deg"),
+    );
+    row("Vacuum range", km(ranges.vacuum_m), "Closed-form, no air");
+    row("Air-drag range", km(ranges.drag_m), "RK4 with Mach-dependent drag");
+    row("Range deficit", km(results.shortfall_m), "Distance minus operational range");
+    row(
+        "Distance / operational range",
+        format!("{:.1}×", results.multiplier),
+        "Haversine distance over operational range",
+    );
+    row(
+        "Excess over operational range",
+        format!("{:.0}%", (results.multiplier - 1.0) * 100.0),
+        "Haversine distance over operational range, minus one",
+    );
+    row("Verdict", format!("**{}**", results.verdict.label()), "Tiered range check");
+    out.push(String::new());
+
+    out.push("| Range tier | Kind | Range | Shortfall | Factor | Source |".to_string());
+    out.push("|------------|------|-------|-----------|--------|--------|".to_string());
+    for tier in &results.tiers {
+        let kind = match (tier.kind, tier.in_verdict) {
+            (TierKind::Declared, _) => "declared",
+            (TierKind::Physics, true) => "physics",
+            (TierKind::Physics, false) => "physics, not counted",
+        };
+        out.push(format!(
+            "| {} | {} | {} | {} | {:.2}× | {} |",
+            tier.name,
+            kind,
+            km(tier.range),
+            if tier.reaches() { "reaches".to_string() } else { km(tier.shortfall) },
+            tier.multiplier,
+            citation(results, &tier.source)
+        ));
+    }
+    out.push(String::new());
+
+    out.push("## Coordinate References".to_string());
+    out.push(String::new());
+    site_lines(&mut out, "Launch Point", &results.inputs.launch);
+    site_lines(&mut out, "Target Point", &results.inputs.target);
+
+    out.push("## Physical Constants".to_string());
+    out.push(String::new());
+    out.push("| Parameter | Value | Unit | Source |".to_string());
+    out.push("|-----------|-------|------|--------|".to_string());
+    let scenario = citation(results, "scenario");
+    let mut row = |parameter: &str, value: String, unit: &str, source: &str| {
+        out.push(format!("| {} | {} | {} | {} |", parameter, value, unit, source));
+    };
+    row(
+        "Earth Radius (Haversine)",
+        thousands(assumptions.earth_radius_m, 0),
+        "meters",
+        citation(results, "iugg"),
+    );
+    row("WGS84 Semi-major Axis", thousands(WGS84_A, 0), "meters", citation(results, "wgs84"));
+    row(
+        "WGS84 Flattening",
+        format!("1/{}", 1.0 / WGS84_F),
+        "-",
+        citation(results, "wgs84"),
+    );
+    row("Gravity", format!("{}", assumptions.gravity_m_s2), "m/s²", scenario);
+    row(
+        &format!("{} Muzzle Velocity", weapon.name),
+        thousands(weapon.muzzle_velocity_m_s, 0),
+        "m/s",
+        field_citation(results, "muzzle_velocity"),
+    );
+    row(
+        &format!("{} Projectile Mass", weapon.name),
+        format!("{}", weapon.projectile_mass_kg),
+        "kg",
+        field_citation(results, "projectile_mass"),
+    );
+    row(
+        &format!("{} Operational Range", weapon.name),
+        thousands(weapon.max_range_operational_m, 0),
+        "meters",
+        field_citation(results, "max_range_operational"),
+    );
+    row(
+        &format!("{} Published Maximum Range", weapon.name),
+        thousands(weapon.max_range_45deg_m, 0),
+        "meters",
+        field_citation(results, "max_range_45deg"),
+    );
+    if let Some(motor) = weapon.motor {
+        row(
+            &format!("{} Burn Time", weapon.name),
+            format!("{}", motor.burn_time_s),
+            "s",
+            field_citation(results, "burn_time"),
+        );
+        row(
+            &format!("{} Propellant Mass", weapon.name),
+            format!("{}", motor.propellant_mass_kg),
+            "kg",
+            field_citation(results, "propellant_mass"),
+        );
+        row(
+            &format!("{} Thrust", weapon.name),
+            thousands(motor.thrust_n, 0),
+            "N",
+            field_citation(results, "thrust"),
+        );
+    }
+    row("Launch Angle", format!("{}", assumptions.launch_angle_deg), "degrees", scenario);
+    row(
+        "Sea-level Temperature",
+        format!("{}", assumptions.atmosphere.sea_level_temperature),
+        "K",
+        citation(results, "icao"),
+    );
+    row(
+        "Sea-level Pressure",
+        thousands(assumptions.atmosphere.sea_level_pressure, 0),
+        "Pa",
+        citation(results, "icao"),
+    );
+
+    out.join("\n") + "\n"
+}
+
+/// Replaces the text between `BEGIN_MARKER` and `END_MARKER` in `text`
+/// with `generated`, leaving the rest of the file untouched.
+pub fn splice(text: &str, generated: &str) -> Result<String, String> {
+    let begin = text
+        .find(BEGIN_MARKER)
+        .ok_or_else(|| format!("no \"{}\" marker found", BEGIN_MARKER))?;
+    let content_start = begin + BEGIN_MARKER.len();
+    let end = text[content_start..]
+        .find(END_MARKER)
+        .map(|i| content_start + i)
+        .ok_or_else(|| format!("no \"{}\" marker after the begin marker", END_MARKER))?;
+    Ok(format!(
+        "{}\n\n{}\n{}",
+        &text[..content_start],
+        generated.trim_end(),
+        &text[end..]
+    ))
+}
@@ -0,0 +1,412 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::{Distribution as _, Normal, Triangular};
+use serde::{Deserialize, Serialize};
+
+use crate::ballistics::VacuumTrajectory;
+use crate::geo::{destination_point, haversine_distance};
+use crate::scenario::Scenario;
+
+/// Probability distribution of an uncertain scenario parameter.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "distribution", rename_all = "lowercase")]
+pub enum Distribution {
+    Normal { mean: f64, std_dev: f64 },
+    Uniform { min: f64, max: f64 },
+    Triangular { min: f64, mode: f64, max: f64 },
+}
+
+impl Distribution {
+    /// Rejects parameters that describe no distribution, such as a negative
+    /// standard deviation or a mode outside `min..=max`.
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            Distribution::Normal { mean, std_dev } => {
+                if !mean.is_finite() || !std_dev.is_finite() {
+                    return Err(format!("normal mean {} and std_dev {} must be numbers", mean, std_dev));
+                }
+                if std_dev < 0.0 {
+                    return Err(format!("normal std_dev {} must not be negative", std_dev));
+                }
+                Normal::new(mean, std_dev)
+                    .map(drop)
+                    .map_err(|e| format!("normal mean {} std_dev {}: {}", mean, std_dev, e))
+            }
+            Distribution::Uniform { min, max } => {
+                if min.is_finite() && max.is_finite() && min <= max {
+                    Ok(())
+                } else {
+                    Err(format!("uniform min {} must not exceed max {}", min, max))
+                }
+            }
+            Distribution::Triangular { min, mode, max } => Triangular::new(min, max, mode)
+                .map(drop)
+                .map_err(|e| format!("triangular min {} mode {} max {}: {}", min, mode, max, e)),
+        }
+    }
+
+    /// Rejects distributions of a positive quantity that reach zero or
+    /// below. A normal one only needs a positive mean, as it is truncated
+    /// at zero when sampled.
+    pub fn validate_positive(&self) -> Result<(), String> {
+        self.validate()?;
+        match *self {
+            Distribution::Normal { mean, .. } if mean <= 0.0 => {
+                Err(format!("normal mean {} must be positive", mean))
+            }
+            Distribution::Uniform { min, .. } | Distribution::Triangular { min, .. } if min <= 0.0 => {
+                Err(format!("min {} must be positive", min))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The distribution built once for drawing many samples.
+    pub fn sampler(&self) -> Result<Sampler, String> {
+        self.validate()?;
+        Ok(match *self {
+            Distribution::Normal { mean, std_dev } => {
+                Sampler::Normal(Normal::new(mean, std_dev).map_err(|e| e.to_string())?)
+            }
+            Distribution::Uniform { min, max } => Sampler::Uniform { min, max },
+            Distribution::Triangular { min, mode, max } => {
+                Sampler::Triangular(Triangular::new(min, max, mode).map_err(|e| e.to_string())?)
+            }
+        })
+    }
+}
+
+/// A validated `Distribution`, ready to draw from.
+#[derive(Debug, Clone, Copy)]
+pub enum Sampler {
+    Normal(Normal<f64>),
+    Uniform { min: f64, max: f64 },
+    Triangular(Triangular<f64>),
+}
+
+impl Sampler {
+    pub fn sample(&self, rng: &mut impl Rng) -> f64 {
+        match self {
+            Sampler::Normal(normal) => normal.sample(rng),
+            Sampler::Uniform { min, max } => min + (max - min) * rng.random::<f64>(),
+            Sampler::Triangular(triangular) => triangular.sample(rng),
+        }
+    }
+
+    /// A sample above zero, redrawing the rest. Ends quickly for any
+    /// distribution passing `Distribution::validate_positive`, where at
+    /// least half of the draws are positive.
+    pub fn sample_positive(&self, rng: &mut impl Rng) -> f64 {
+        loop {
+            let value = self.sample(rng);
+            if value > 0.0 {
+                return value;
+            }
+        }
+    }
+}
+
+/// `[uncertainty]` section of a scenario. Weapon parameters without a
+/// distribution stay fixed at their scenario value.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UncertaintyConfig {
+    #[serde(default = "default_samples")]
+    pub samples: usize,
+    #[serde(default)]
+    pub seed: u64,
+    pub max_range_operational: Option<Distribution>,
+    pub muzzle_velocity: Option<Distribution>,
+}
+
+fn default_samples() -> usize {
+    10000
+}
+
+impl UncertaintyConfig {
+    /// Checks every weapon distribution, naming the parameter on error.
+    pub fn validate(&self) -> Result<(), String> {
+        self.samplers().map(drop)
+    }
+
+    /// Samplers for the range and the muzzle velocity, both positive
+    /// quantities; `None` where the parameter is fixed.
+    fn samplers(&self) -> Result<(Option<Sampler>, Option<Sampler>), String> {
+        let sampler = |name: &str, distribution: &Option<Distribution>| {
+            distribution
+                .as_ref()
+                .map(|d| d.validate_positive().and_then(|()| d.sampler()))
+                .transpose()
+                .map_err(|e| format!("uncertainty.{}: {}", name, e))
+        };
+        Ok((
+            sampler("max_range_operational", &self.max_range_operational)?,
+            sampler("muzzle_velocity", &self.muzzle_velocity)?,
+        ))
+    }
+}
+
+impl Default for UncertaintyConfig {
+    fn default() -> Self {
+        UncertaintyConfig {
+            samples: default_samples(),
+            seed: 0,
+            max_range_operational: None,
+            muzzle_velocity: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Percentiles {
+    pub min: f64,
+    pub p05: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub max: f64,
+}
+
+impl Percentiles {
+    fn from_sorted(sorted: &[f64]) -> Self {
+        let at = |q: f64| sorted[((sorted.len() - 1) as f64 * q).round() as usize];
+        Percentiles {
+            min: sorted[0],
+            p05: at(0.05),
+            p50: at(0.5),
+            p95: at(0.95),
+            max: sorted[sorted.len() - 1],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MonteCarloResult {
+    pub samples: usize,
+    pub seed: u64,
+    /// Share of samples where the distance is within the sampled operational range.
+    pub probability_within_operational: f64,
+    /// Share of samples where the distance is within the sampled vacuum range
+    /// at the optimal launch angle, the loosest physical bound.
+    pub probability_within_vacuum: f64,
+    pub distance: Percentiles,
+    pub max_range_operational: Percentiles,
+    pub vacuum_range: Percentiles,
+    pub shortfall: Percentiles,
+    #[serde(skip)]
+    pub distances: Vec<f64>,
+    #[serde(skip)]
+    pub ranges: Vec<f64>,
+}
+
+/// Samples launch and target uniformly within their uncertainty radii and the
+/// weapon parameters from their declared distributions.
+pub fn run(scenario: &Scenario) -> Result<MonteCarloResult, String> {
+    let config = &scenario.uncertainty;
+    let (range_sampler, velocity_sampler) = config.samplers()?;
+    let samples = config.samples.max(1);
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let altitudes = scenario.altitudes();
+
+    let mut distances = Vec::with_capacity(samples);
+    let mut ranges = Vec::with_capacity(samples);
+    let mut vacuum_ranges = Vec::with_capacity(samples);
+    let mut within_operational = 0;
+    let mut within_vacuum = 0;
+
+    for _ in 0..samples {
+        let (lat1, lon1) = sample_site(&mut rng, scenario.launch.lat, scenario.launch.lon, scenario.launch.uncertainty);
+        let (lat2, lon2) = sample_site(&mut rng, scenario.target.lat, scenario.target.lon, scenario.target.uncertainty);
+        let distance = haversine_distance(lat1, lon1, lat2, lon2);
+
+        let range = range_sampler
+            .as_ref()
+            .map_or(scenario.weapon.max_range_operational, |s| s.sample_positive(&mut rng));
+        let v0 = velocity_sampler
+            .as_ref()
+            .map_or(scenario.weapon.muzzle_velocity, |s| s.sample_positive(&mut rng));
+        let vacuum_range = VacuumTrajectory::optimal(v0, scenario.physics.gravity, altitudes).range();
+
+        if distance <= range {
+            within_operational += 1;
+        }
+        if distance <= vacuum_range {
+            within_vacuum += 1;
+        }
+        distances.push(distance);
+        ranges.push(range);
+        vacuum_ranges.push(vacuum_range);
+    }
+
+    let mut shortfalls: Vec<f64> = distances.iter().zip(&ranges).map(|(d, r)| d - r).collect();
+    let percentiles = |values: &mut Vec<f64>| {
+        values.sort_by(f64::total_cmp);
+        Percentiles::from_sorted(values)
+    };
+    let shortfall = percentiles(&mut shortfalls);
+    let vacuum_range = percentiles(&mut vacuum_ranges);
+    let distance = percentiles(&mut distances);
+    let max_range_operational = percentiles(&mut ranges);
+
+    Ok(MonteCarloResult {
+        samples,
+        seed: config.seed,
+        probability_within_operational: within_operational as f64 / samples as f64,
+        probability_within_vacuum: within_vacuum as f64 / samples as f64,
+        distance,
+        max_range_operational,
+        vacuum_range,
+        shortfall,
+        distances,
+        ranges,
+    })
+}
+
+/// Uniform sample inside a circle of `radius` meters around a point.
+fn sample_site(rng: &mut impl Rng, lat: f64, lon: f64, radius: f64) -> (f64, f64) {
+    if radius <= 0.0 {
+        return (lat, lon);
+    }
+    let distance = radius * rng.random::<f64>().sqrt();
+    let bearing = 360.0 * rng.random::<f64>();
+    destination_point(lat, lon, bearing, distance)
+}
+
+/// Probability as text, without rounding a non-zero share down to zero.
+pub fn format_probability(p: f64, samples: usize) -> String {
+    if p == 0.0 {
+        format!("< {:.4}% (0 of {} samples)", 100.0 / samples as f64, samples)
+    } else {
+        format!("{:.2}%", p * 100.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenario(samples: usize) -> Scenario {
+        let mut scenario = Scenario::default();
+        scenario.launch.uncertainty = 500.0;
+        scenario.target.uncertainty = 500.0;
+        scenario.uncertainty = UncertaintyConfig {
+            samples,
+            seed: 7,
+            max_range_operational: Some(Distribution::Uniform {
+                min: 19000.0,
+                max: 21000.0,
+            }),
+            muzzle_velocity: Some(Distribution::Normal {
+                mean: 690.0,
+                std_dev: 10.0,
+            }),
+        };
+        scenario
+    }
+
+    #[test]
+    fn rejects_invalid_distributions() {
+        let invalid = [
+            Distribution::Normal { mean: 690.0, std_dev: -1.0 },
+            Distribution::Normal { mean: f64::NAN, std_dev: 1.0 },
+            Distribution::Uniform { min: 2.0, max: 1.0 },
+            Distribution::Triangular { min: 0.0, mode: 5.0, max: 4.0 },
+        ];
+        for distribution in invalid {
+            assert!(distribution.validate().is_err(), "{:?}", distribution);
+            assert!(distribution.sampler().is_err(), "{:?}", distribution);
+        }
+
+        let mut scenario = scenario(10);
+        scenario.uncertainty.muzzle_velocity = Some(Distribution::Normal { mean: 690.0, std_dev: -1.0 });
+        let error = run(&scenario).unwrap_err();
+        assert!(error.starts_with("uncertainty.muzzle_velocity: "), "{}", error);
+    }
+
+    #[test]
+    fn samples_stay_within_their_support() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let uniform = Distribution::Uniform { min: 10.0, max: 20.0 }.sampler().unwrap();
+        let triangular = Distribution::Triangular { min: 10.0, mode: 12.0, max: 20.0 }.sampler().unwrap();
+        for _ in 0..1000 {
+            assert!((10.0..=20.0).contains(&uniform.sample(&mut rng)));
+            assert!((10.0..=20.0).contains(&triangular.sample(&mut rng)));
+        }
+
+        let normal = Distribution::Normal { mean: 690.0, std_dev: 10.0 }.sampler().unwrap();
+        let n = 20000;
+        let mean = (0..n).map(|_| normal.sample(&mut rng)).sum::<f64>() / n as f64;
+        assert!((mean - 690.0).abs() < 0.5, "{}", mean);
+    }
+
+    #[test]
+    fn velocity_and_range_samples_stay_positive() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let wide = Distribution::Normal { mean: 10.0, std_dev: 100.0 }.sampler().unwrap();
+        assert!((0..1000).all(|_| wide.sample_positive(&mut rng) > 0.0));
+
+        for (field, distribution) in [
+            ("muzzle_velocity", Distribution::Normal { mean: -5.0, std_dev: 1.0 }),
+            ("muzzle_velocity", Distribution::Uniform { min: 0.0, max: 700.0 }),
+            ("max_range_operational", Distribution::Triangular { min: -1.0, mode: 5.0, max: 10.0 }),
+        ] {
+            let mut scenario = scenario(10);
+            match field {
+                "muzzle_velocity" => scenario.uncertainty.muzzle_velocity = Some(distribution),
+                _ => scenario.uncertainty.max_range_operational = Some(distribution),
+            }
+            let error = run(&scenario).unwrap_err();
+            assert!(error.starts_with(&format!("uncertainty.{}: ", field)), "{}", error);
+        }
+    }
+
+    #[test]
+    fn site_samples_stay_within_the_radius() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..1000 {
+            let (lat, lon) = sample_site(&mut rng, 14.3, 103.5, 500.0);
+            assert!(haversine_distance(14.3, 103.5, lat, lon) <= 500.0 + 1e-6);
+        }
+        assert_eq!(sample_site(&mut rng, 14.3, 103.5, 0.0), (14.3, 103.5));
+    }
+
+    #[test]
+    fn a_fixed_seed_repeats_the_run() {
+        let a = run(&scenario(500)).unwrap();
+        let b = run(&scenario(500)).unwrap();
+        assert_eq!(a.distances, b.distances);
+        assert_eq!(a.ranges, b.ranges);
+        assert_eq!(a.probability_within_vacuum, b.probability_within_vacuum);
+
+        let result = a;
+        assert_eq!(result.samples, 500);
+        assert!((0.0..=1.0).contains(&result.probability_within_operational));
+        assert!(result.probability_within_operational <= result.probability_within_vacuum);
+        let d = result.distance;
+        assert!(d.min <= d.p05 && d.p05 <= d.p50 && d.p50 <= d.p95 && d.p95 <= d.max);
+        assert!(d.min < d.max);
+    }
+
+    #[test]
+    fn without_uncertainty_every_sample_is_the_deterministic_case() {
+        let mut scenario = Scenario::default();
+        scenario.launch.uncertainty = 0.0;
+        scenario.target.uncertainty = 0.0;
+        scenario.uncertainty = UncertaintyConfig {
+            samples: 20,
+            ..UncertaintyConfig::default()
+        };
+        assert!(!scenario.has_uncertainty());
+
+        let result = run(&scenario).unwrap();
+        let distance = haversine_distance(
+            scenario.launch.lat,
+            scenario.launch.lon,
+            scenario.target.lat,
+            scenario.target.lon,
+        );
+        assert_eq!(result.distance.min, distance);
+        assert_eq!(result.distance.max, distance);
+        assert_eq!(result.max_range_operational.p50, scenario.weapon.max_range_operational);
+        let expected = if distance <= scenario.weapon.max_range_operational { 1.0 } else { 0.0 };
+        assert_eq!(result.probability_within_operational, expected);
+    }
+}
@@ -0,0 +1,306 @@
+use plotters::prelude::*;
+use printpdf::{
+    BuiltinFont, ColorBits, ColorSpace, Image, ImageTransform, ImageXObject, IndirectFontRef, Mm,
+    PdfDocument, PdfDocumentReference, PdfLayerReference, Px,
+};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use crate::analysis::Analysis;
+use crate::document::{self, Block, Chart};
+use crate::render;
+use crate::scenario::Scenario;
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+const TEXT_WIDTH: f32 = PAGE_WIDTH - 2.0 * MARGIN;
+const PT_TO_MM: f32 = 0.3528;
+const CHART_SIZE: (u32, u32) = (1600, 900);
+
+#[derive(Clone, Copy)]
+enum Style {
+    Regular,
+    Bold,
+    Mono,
+    MonoBold,
+}
+
+impl Style {
+    /// Average glyph width as a fraction of the font size, for wrapping.
+    fn char_width(&self) -> f32 {
+        match self {
+            Style::Regular => 0.5,
+            Style::Bold => 0.55,
+            Style::Mono | Style::MonoBold => 0.6,
+        }
+    }
+}
+
+/// Builtin PDF fonts only cover Windows-1252, so Greek letters, subscripts
+/// and other symbols in the formulas are spelled out.
+fn ascii(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            c if c.is_ascii() || ('\u{a0}'..='\u{ff}').contains(&c) => out.push(c),
+            'θ' => out.push_str("theta"),
+            'ṁ' => out.push_str("m_dot"),
+            'μ' => out.push_str("mu"),
+            'π' => out.push_str("pi"),
+            'σ' => out.push_str("sigma"),
+            'φ' => out.push_str("phi"),
+            'λ' => out.push_str("lambda"),
+            'ρ' => out.push_str("rho"),
+            'Δ' => out.push('d'),
+            '√' => out.push_str("sqrt"),
+            '−' | '–' | '—' => out.push('-'),
+            '≤' => out.push_str("<="),
+            '≥' => out.push_str(">="),
+            '≈' => out.push('~'),
+            '≠' => out.push_str("!="),
+            '∞' => out.push_str("inf"),
+            '•' => out.push('*'),
+            '…' => out.push_str("..."),
+            '‘' | '’' => out.push('\''),
+            '“' | '”' => out.push('"'),
+            '₀'..='₉' => out.push(char::from(b'0' + (c as u32 - '₀' as u32) as u8)),
+            _ => out.push('?'),
+        }
+    }
+    out
+}
+
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word = word.to_string();
+        while word.chars().count() > width {
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            let split: String = word.chars().take(width).collect();
+            word = word.chars().skip(width).collect();
+            lines.push(split);
+        }
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&word);
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+struct Writer {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    fonts: [IndirectFontRef; 4],
+    y: f32,
+}
+
+impl Writer {
+    fn new(title: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let (doc, page, layer) = PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+        let fonts = [
+            doc.add_builtin_font(BuiltinFont::Helvetica)?,
+            doc.add_builtin_font(BuiltinFont::HelveticaBold)?,
+            doc.add_builtin_font(BuiltinFont::Courier)?,
+            doc.add_builtin_font(BuiltinFont::CourierBold)?,
+        ];
+        let layer = doc.get_page(page).get_layer(layer);
+        Ok(Writer {
+            doc,
+            layer,
+            fonts,
+            y: PAGE_HEIGHT - MARGIN,
+        })
+    }
+
+    fn new_page(&mut self) {
+        let (page, layer) = self.doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    /// Starts a new page unless `height` millimeters still fit on this one.
+    fn ensure(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            self.new_page();
+        }
+    }
+
+    fn gap(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    fn line_height(size: f32) -> f32 {
+        size * 1.35 * PT_TO_MM
+    }
+
+    fn chars_per_line(style: Style, size: f32, width: f32) -> usize {
+        (width / (size * style.char_width() * PT_TO_MM)) as usize
+    }
+
+    fn line(&mut self, text: &str, style: Style, size: f32, indent: f32) {
+        let height = Self::line_height(size);
+        self.ensure(height);
+        self.y -= height;
+        let font = &self.fonts[style as usize];
+        self.layer.use_text(ascii(text), size, Mm(MARGIN + indent), Mm(self.y), font);
+    }
+
+    fn text(&mut self, text: &str, style: Style, size: f32, indent: f32) {
+        let width = Self::chars_per_line(style, size, TEXT_WIDTH - indent);
+        for line in wrap(text, width) {
+            self.line(&line, style, size, indent);
+        }
+    }
+
+    fn table(&mut self, header: &[String], rows: &[Vec<String>]) {
+        const SIZE: f32 = 8.0;
+        let available = Self::chars_per_line(Style::Mono, SIZE, TEXT_WIDTH);
+        let columns = header.len();
+        let mut widths: Vec<usize> = (0..columns)
+            .map(|c| {
+                rows.iter()
+                    .filter_map(|r| r.get(c))
+                    .chain(std::iter::once(&header[c]))
+                    .map(|cell| cell.chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        // Two spaces between columns; shrink the widest column until it fits.
+        while widths.iter().sum::<usize>() + 2 * (columns - 1) > available {
+            let widest = (0..columns).max_by_key(|&c| widths[c]).unwrap_or(0);
+            if widths[widest] <= 8 {
+                break;
+            }
+            widths[widest] -= 1;
+        }
+
+        let write_row = |writer: &mut Self, cells: &[String], style: Style| {
+            let wrapped: Vec<Vec<String>> = (0..columns)
+                .map(|c| wrap(cells.get(c).map(String::as_str).unwrap_or(""), widths[c]))
+                .collect();
+            let height = wrapped.iter().map(Vec::len).max().unwrap_or(1);
+            for i in 0..height {
+                let line: Vec<String> = (0..columns)
+                    .map(|c| {
+                        let cell = wrapped[c].get(i).map(String::as_str).unwrap_or("");
+                        format!("{:<width$}", cell, width = widths[c])
+                    })
+                    .collect();
+                writer.line(line.join("  ").trim_end(), style, SIZE, 0.0);
+            }
+        };
+        write_row(self, header, Style::MonoBold);
+        let rule: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+        self.line(&rule.join("  "), Style::Mono, SIZE, 0.0);
+        for row in rows {
+            write_row(self, row, Style::Mono);
+        }
+    }
+
+    fn chart(&mut self, chart: Chart, scenario: &Scenario, analysis: &Analysis) -> Result<(), Box<dyn std::error::Error>> {
+        let (width, height) = CHART_SIZE;
+        let mut buffer = vec![0u8; (width * height * 3) as usize];
+        {
+            let root = BitMapBackend::with_buffer(&mut buffer, CHART_SIZE).into_drawing_area();
+            match chart {
+                Chart::Trajectory => render::trajectory_chart(&root, scenario, analysis)?,
+                Chart::RangeTiers => render::tier_chart(&root, analysis)?,
+                Chart::Map => render::map_chart(&root, scenario, analysis)?,
+            }
+        }
+        let dpi = width as f32 / (TEXT_WIDTH / 25.4);
+        let image_height = height as f32 / dpi * 25.4;
+        self.ensure(image_height + Self::line_height(9.0));
+        self.y -= image_height;
+        Image::from(ImageXObject {
+            width: Px(width as usize),
+            height: Px(height as usize),
+            color_space: ColorSpace::Rgb,
+            bits_per_component: ColorBits::Bit8,
+            interpolate: true,
+            image_data: buffer,
+            image_filter: None,
+            smask: None,
+            clipping_bbox: None,
+        })
+        .add_to_layer(
+            self.layer.clone(),
+            ImageTransform {
+                translate_x: Some(Mm(MARGIN)),
+                translate_y: Some(Mm(self.y)),
+                dpi: Some(dpi),
+                ..Default::default()
+            },
+        );
+        self.line(chart.title(), Style::Regular, 9.0, 0.0);
+        Ok(())
+    }
+}
+
+/// Writes the long-form report as a PDF with builtin fonts and raster charts.
+pub fn pdf(scenario: &Scenario, analysis: &Analysis, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let doc = document::build(scenario, analysis);
+    let mut writer = Writer::new(&doc.title)?;
+    writer.text(&doc.title, Style::Bold, 16.0, 0.0);
+
+    for section in &doc.sections {
+        writer.gap(4.0);
+        writer.ensure(Writer::line_height(13.0) * 3.0);
+        writer.text(&section.title, Style::Bold, 13.0, 0.0);
+        writer.gap(1.0);
+        for block in &section.blocks {
+            match block {
+                Block::Paragraph(text) => writer.text(text, Style::Regular, 10.0, 0.0),
+                Block::Formula(formula, meaning) => {
+                    writer.text(formula, Style::Mono, 9.0, 4.0);
+                    writer.text(meaning, Style::Regular, 9.0, 4.0);
+                }
+                Block::Table { header, rows } => writer.table(header, rows),
+                Block::List(items) => {
+                    for item in items {
+                        let width = Writer::chars_per_line(Style::Regular, 10.0, TEXT_WIDTH - 4.0);
+                        for (i, line) in wrap(item, width).iter().enumerate() {
+                            if i == 0 {
+                                writer.line(&format!("- {}", line), Style::Regular, 10.0, 0.0);
+                            } else {
+                                writer.line(line, Style::Regular, 10.0, 3.5);
+                            }
+                        }
+                    }
+                }
+                Block::Chart(chart) => writer.chart(*chart, scenario, analysis)?,
+            }
+            writer.gap(2.0);
+        }
+    }
+
+    let file = File::create(path).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+    writer.doc.save(&mut BufWriter::new(file))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_spells_out_symbols_outside_windows_1252() {
+        assert_eq!(ascii("m(t) = m₀ − ṁ · t"), "m(t) = m0 - m_dot · t");
+        assert_eq!(ascii("ρ(h) · Cd(M) ≤ 2π"), "rho(h) · Cd(M) <= 2pi");
+        assert_eq!(ascii("45° ±50 m × 2"), "45° ±50 m × 2");
+        assert!(!ascii("d = 2R · arcsin(√(sin²(Δφ/2)))").contains('?'));
+    }
+}
@@ -0,0 +1,1398 @@
+use opencv::{core, imgproc};
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+use std::thread;
+
+use crate::analysis::{Analysis, TierKind, Verdict};
+use crate::basemap::Basemap;
+use crate::encode::{self, FrameFormat};
+use crate::geo::EARTH_RADIUS;
+use crate::map::{self, MapProjection};
+use crate::monte_carlo::{MonteCarloResult, format_probability};
+use crate::report;
+use crate::scenario::{OutputOptions, Scenario};
+
+const HISTOGRAM_SIZE: (u32, u32) = (1920, 1080);
+/// Declared range tiers on the chart, operational range first.
+const TIER_COLORS: [RGBColor; 4] = [
+    RGBColor(0, 255, 0),
+    RGBColor(255, 140, 0),
+    RGBColor(160, 0, 200),
+    RGBColor(120, 80, 40),
+];
+/// Ground under the side view.
+const TERRAIN_COLOR: RGBColor = RGBColor(176, 150, 105);
+
+/// Green within the operational range, orange within a declared range, red
+/// beyond every declared range.
+fn verdict_color(verdict: Verdict) -> RGBColor {
+    match verdict {
+        Verdict::WithinOperational => RGBColor(0, 150, 0),
+        Verdict::WithinDeclared => RGBColor(255, 140, 0),
+        Verdict::PhysicsOnly | Verdict::Impossible => RGBColor(200, 0, 0),
+    }
+}
+
+/// Terrain profile in kilometers and meters, up to just past `x_max` km.
+fn terrain_km(scenario: &Scenario, x_max: f64) -> Vec<(f64, f64)> {
+    let Some(profile) = &scenario.terrain_profile else {
+        return Vec::new();
+    };
+    let mut points = Vec::new();
+    for &(distance, elevation) in &profile.points {
+        points.push((distance / 1000.0, elevation));
+        if distance / 1000.0 > x_max {
+            break;
+        }
+    }
+    points
+}
+
+/// Where the vacuum and drag trajectories meet a ridge they would otherwise
+/// clear, in kilometers and meters.
+fn blocked_impacts_km(analysis: &Analysis) -> Vec<(f64, f64)> {
+    [analysis.vacuum_impact, analysis.drag().impact()]
+        .iter()
+        .filter(|impact| impact.blocked())
+        .map(|impact| (impact.range / 1000.0, impact.elevation))
+        .collect()
+}
+
+/// Frame geometry. Sizes in the drawing code are tuned for 1920x1080 and
+/// multiplied by the scales here; portrait frames put the legend under the
+/// chart instead of beside it.
+#[derive(Debug, Clone, Copy)]
+struct Layout {
+    width: u32,
+    height: u32,
+}
+
+impl Layout {
+    fn new(output: &OutputOptions) -> Self {
+        Layout {
+            width: output.width,
+            height: output.height,
+        }
+    }
+
+    fn portrait(&self) -> bool {
+        self.height > self.width
+    }
+
+    /// Width of the chart beside the legend, or height of the chart above it.
+    fn chart_split(&self) -> u32 {
+        if self.portrait() {
+            self.height * 11 / 20
+        } else {
+            self.width * 1350 / 1920
+        }
+    }
+
+    fn chart_scale(&self) -> f64 {
+        let (width, height) = if self.portrait() {
+            (self.width, self.chart_split())
+        } else {
+            (self.chart_split(), self.height)
+        };
+        (width as f64 / 1350.0).min(height as f64 / 1080.0)
+    }
+
+    fn legend_scale(&self) -> f64 {
+        let (width, height) = if self.portrait() {
+            (self.width, self.height - self.chart_split())
+        } else {
+            (self.width - self.chart_split(), self.height)
+        };
+        (width as f64 / 570.0).min(height as f64 / 1080.0)
+    }
+
+    /// Scale of the proof card, against a 1920x1080 card or its portrait
+    /// counterpart.
+    fn card_scale(&self) -> f64 {
+        let (width, height) = (self.width as f64, self.height as f64);
+        if self.portrait() {
+            (width / 1080.0).min(height / 1920.0)
+        } else {
+            (width / 1920.0).min(height / 1080.0)
+        }
+    }
+}
+
+/// `value` scaled, rounded and at least 1.
+fn scaled(value: f64, scale: f64) -> u32 {
+    ((value * scale).round() as u32).max(1)
+}
+
+pub fn render(
+    scenario: &Scenario,
+    analysis: &Analysis,
+    output_dir: &Path,
+    jobs: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let fps = scenario.output.fps;
+    let layout = Layout::new(&scenario.output);
+    let animation = Animation::new(scenario, analysis);
+
+    fs::create_dir_all(output_dir)?;
+    let frame_dir = output_dir.join(&scenario.output.frame_dir);
+    if scenario.output.dump_frames {
+        fs::create_dir_all(&frame_dir)?;
+    }
+
+    let output_video = output_dir.join(&scenario.output.video);
+    let format = scenario.output.video_format()?;
+    let frame_format = FrameFormat {
+        width: layout.width,
+        height: layout.height,
+        fps,
+    };
+    // One write per animation frame plus the map and the proof card.
+    let mut sink = encode::open(format, &output_video, frame_format, animation.total_frames + 2)?;
+
+    // Render the next batch of frames on worker threads while the current
+    // batch is encoded, so frames reach the encoder in order.
+    let jobs = jobs.max(1);
+    let batch = jobs * 2;
+    let total_frames = animation.total_frames;
+    let mut start = 0;
+    let mut frames = render_frames(&animation, 0..batch.min(total_frames), jobs)?;
+    while !frames.is_empty() {
+        let next_start = start + frames.len();
+        let next = next_start..(next_start + batch).min(total_frames);
+        let (next_frames, written) = thread::scope(|scope| {
+            let worker = scope.spawn(|| render_frames(&animation, next, jobs));
+            let written = frames.iter().enumerate().try_for_each(
+                |(offset, buffer)| -> Result<(), Box<dyn std::error::Error>> {
+                    if scenario.output.dump_frames {
+                        let frame_path = frame_dir.join(format!("frame_{:05}.png", start + offset));
+                        encode::write_png(&frame_path, buffer, layout.width, layout.height)?;
+                    }
+                    sink.write(buffer, 1)
+                },
+            );
+            (worker.join(), written)
+        });
+        written?;
+        frames = next_frames.map_err(|_| "frame rendering thread panicked")??;
+        start = next_start;
+    }
+
+    let mut map_img = vec![0u8; (layout.width * layout.height * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut map_img, (layout.width, layout.height)).into_drawing_area();
+        map_chart(&root, scenario, analysis)?;
+    }
+    sink.write(&map_img, fps * 3)?;
+
+    let proof_img = encode::mat_to_rgb(&proof_card(scenario, analysis)?)?;
+    if scenario.output.dump_frames {
+        let map_path = frame_dir.join("map.png");
+        encode::write_png(&map_path, &map_img, layout.width, layout.height)?;
+        let proof_path = frame_dir.join("proof_card.png");
+        encode::write_png(&proof_path, &proof_img, layout.width, layout.height)?;
+        println!("🖼️  Frames saved in: {}", frame_dir.display());
+    }
+    sink.write(&proof_img, fps * 3)?;
+    sink.finish()?;
+
+    println!("📁 Video saved as: {} ({})", output_video.display(), format.name());
+
+    Ok(())
+}
+
+/// Renders `frames` as RGB buffers, split across `jobs` threads, in order.
+fn render_frames(
+    animation: &Animation,
+    frames: Range<usize>,
+    jobs: usize,
+) -> Result<Vec<Vec<u8>>, String> {
+    let count = frames.len();
+    let mut buffers = vec![Vec::new(); count];
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs.min(count))
+            .map(|worker| {
+                let frames = frames.clone();
+                scope.spawn(move || -> Result<Vec<(usize, Vec<u8>)>, String> {
+                    let layout = animation.layout;
+                    let mut rendered = Vec::new();
+                    for offset in (worker..count).step_by(jobs) {
+                        let mut buffer = vec![0u8; (layout.width * layout.height * 3) as usize];
+                        {
+                            let root = BitMapBackend::with_buffer(&mut buffer, (layout.width, layout.height))
+                                .into_drawing_area();
+                            animation
+                                .draw_frame(&root, frames.start + offset)
+                                .and_then(|_| root.present().map_err(|e| e.into()))
+                                .map_err(|e| format!("frame {}: {}", frames.start + offset, e))?;
+                        }
+                        rendered.push((offset, buffer));
+                    }
+                    Ok(rendered)
+                })
+            })
+            .collect();
+        for worker in workers {
+            let rendered = worker.join().map_err(|_| "frame rendering thread panicked".to_string())??;
+            for (offset, buffer) in rendered {
+                buffers[offset] = buffer;
+            }
+        }
+        Ok(buffers)
+    })
+}
+
+/// Chart scales and sampled trajectories shared by every animation frame.
+struct Animation<'a> {
+    scenario: &'a Scenario,
+    analysis: &'a Analysis,
+    layout: Layout,
+    total_frames: usize,
+    trajectory_resolution: usize,
+    chart_x_max: f64,
+    chart_y_max: f64,
+    trajectory_points: Vec<(f64, f64)>,
+    drag_points_km: Vec<(f64, f64)>,
+    terrain_points_km: Vec<(f64, f64)>,
+    animation_points: Vec<(f64, f64)>,
+}
+
+impl<'a> Animation<'a> {
+    fn new(scenario: &'a Scenario, analysis: &'a Analysis) -> Self {
+        let actual_distance = analysis.actual_distance;
+        let total_frames = scenario.output.fps * scenario.output.duration;
+        let range_theoretical = analysis.range_theoretical;
+        let max_h = analysis.max_h;
+
+        let max_distance = actual_distance
+            .max(range_theoretical)
+            .max(analysis.most_generous_tier().range);
+
+        let chart_x_max = if range_theoretical < max_distance {
+            (range_theoretical * 2.5).max(25000.0)
+        } else {
+            (max_distance * 1.1).max(25000.0)
+        };
+
+        let terrain_points_km = terrain_km(scenario, chart_x_max / 1000.0);
+        let ground = terrain_points_km
+            .iter()
+            .map(|(_, h)| *h)
+            .fold(analysis.altitudes.launch, f64::max);
+        let chart_y_max = (max_h * 1.5).max(800.0) + ground.max(0.0);
+
+        // The vacuum trajectory is relative to the launch site; the chart
+        // shows altitude above sea level.
+        let trajectory_resolution = total_frames * 2;
+        let trajectory_points: Vec<(f64, f64)> = analysis
+            .trajectory
+            .sample_until(trajectory_resolution, analysis.vacuum_impact.time)
+            .iter()
+            .map(|(x, y)| (*x, y + analysis.altitudes.launch))
+            .collect();
+        let drag_points_km: Vec<(f64, f64)> = analysis
+            .drag()
+            .sample(trajectory_resolution)
+            .iter()
+            .map(|(x, y)| (*x / 1000.0, *y))
+            .collect();
+
+        let mut animation_points = Vec::with_capacity(total_frames);
+        for i in 0..total_frames {
+            let idx = (i * trajectory_resolution / total_frames).min(trajectory_resolution - 1);
+            animation_points.push(trajectory_points[idx]);
+        }
+
+        Animation {
+            scenario,
+            analysis,
+            layout: Layout::new(&scenario.output),
+            total_frames,
+            trajectory_resolution,
+            chart_x_max,
+            chart_y_max,
+            trajectory_points,
+            drag_points_km,
+            terrain_points_km,
+            animation_points,
+        }
+    }
+
+    /// Draws animation frame `i` of `total_frames` onto `root`.
+    fn draw_frame<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        i: usize,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        DB::ErrorType: 'static,
+    {
+        let scenario = self.scenario;
+        let analysis = self.analysis;
+        let weapon = &scenario.weapon;
+        let launch = &scenario.launch;
+        let target = &scenario.target;
+
+        let actual_distance = analysis.actual_distance;
+        let g = scenario.physics.gravity;
+        let v0 = weapon.muzzle_velocity;
+        let optimal_angle = scenario.physics.launch_angle;
+        let theta = analysis.trajectory.theta;
+        let total_frames = self.total_frames;
+        let trajectory_resolution = self.trajectory_resolution;
+        // The panel works the level-ground formula through with numbers.
+        let level_range = analysis.trajectory.level_range();
+        let range_shortfall = analysis.range_shortfall;
+        let range_multiplier = analysis.range_multiplier;
+        let chart_x_max = self.chart_x_max;
+        let chart_y_max = self.chart_y_max;
+        let trajectory_points = &self.trajectory_points;
+        let drag_points_km = &self.drag_points_km;
+        let animation_points = &self.animation_points;
+        let cs = self.layout.chart_scale();
+        let ls = self.layout.legend_scale();
+        let px = move |value: f64| scaled(value, cs);
+
+        let declared_tiers: Vec<_> = analysis
+            .tiers
+            .iter()
+            .filter(|t| t.kind == TierKind::Declared)
+            .collect();
+        let target_distance_line = [(actual_distance, 0.0), (actual_distance, chart_y_max * 0.8)];
+
+        root.fill(&WHITE)?;
+
+        let (chart_area, legend_area) = if self.layout.portrait() {
+            root.split_vertically(self.layout.chart_split())
+        } else {
+            root.split_horizontally(self.layout.chart_split())
+        };
+        let legend_margin = scaled(15.0, ls);
+        let legend_area = legend_area.margin(legend_margin, legend_margin, legend_margin, legend_margin);
+
+        let mut chart = ChartBuilder::on(&chart_area)
+            .caption(
+                format!(
+                    "{} {}-{}: Range Analysis",
+                    weapon.name,
+                    launch.country.to_uppercase(),
+                    target.country.to_uppercase()
+                ),
+                ("Arial", px(60.0)).into_font().style(FontStyle::Bold).color(&RED),
+            )
+            .margin(px(60.0))
+            .x_label_area_size(px(90.0))
+            .y_label_area_size(px(105.0))
+            .build_cartesian_2d(0f64..(chart_x_max / 1000.0), 0f64..chart_y_max)?;
+
+        chart
+            .configure_mesh()
+            .x_desc("Distance (kilometers)")
+            .y_desc("Altitude (meters)")
+            .axis_desc_style(("Arial", px(42.0)))
+            .label_style(("Arial", px(18.0)))
+            .draw()?;
+
+        if !self.terrain_points_km.is_empty() {
+            chart
+                .draw_series(AreaSeries::new(
+                    self.terrain_points_km.clone(),
+                    0.0,
+                    TERRAIN_COLOR.mix(0.6),
+                ).border_style(TERRAIN_COLOR.stroke_width(px(2.0))))?
+                .label("Terrain (SRTM)")
+                .legend(move |(x, y)| Rectangle::new([(x, y - px(5.0) as i32), (x + px(10.0) as i32, y + px(5.0) as i32)], TERRAIN_COLOR.mix(0.6).filled()));
+        }
+
+        let trajectory_points_km: Vec<(f64, f64)> = trajectory_points
+            .iter()
+            .map(|(x, y)| (*x / 1000.0, *y))
+            .collect();
+    
+        chart
+            .draw_series(LineSeries::new(
+                trajectory_points_km.clone(),
+                BLUE.mix(0.3).stroke_width(px(2.0)),
+            ))?
+            .label("Full Trajectory Path")
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + px(10.0) as i32, y)], BLUE.mix(0.3).stroke_width(px(2.0))));
+
+        chart
+            .draw_series(LineSeries::new(
+                drag_points_km.clone(),
+                CYAN.stroke_width(px(3.0)),
+            ))?
+            .label(format!("With Air Drag ({:.1}km)", analysis.drag().range / 1000.0))
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + px(10.0) as i32, y)], CYAN.stroke_width(px(3.0))));
+
+        let blocked = blocked_impacts_km(analysis);
+        if !blocked.is_empty() {
+            chart
+                .draw_series(blocked.into_iter().map(|point| Cross::new(point, px(10.0), BLACK.stroke_width(px(3.0)))))?
+                .label("Blocked by terrain")
+                .legend(move |(x, y)| Cross::new((x + px(5.0) as i32, y), px(5.0), BLACK.stroke_width(px(2.0))));
+        }
+   
+        let animation_progress = ((i + 1) as f64 / total_frames as f64 * trajectory_resolution as f64) as usize;
+        let current_trajectory_km: Vec<(f64, f64)> = trajectory_points
+            .iter()
+            .take(animation_progress.min(trajectory_points.len()))
+            .map(|(x, y)| (*x / 1000.0, *y))
+            .collect();
+
+        chart
+            .draw_series(LineSeries::new(current_trajectory_km, BLUE.stroke_width(px(6.0))))?
+            .label("Active Trajectory")
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + px(10.0) as i32, y)], BLUE.stroke_width(px(6.0))));
+ 
+        for (idx, tier) in declared_tiers.iter().enumerate() {
+            let color = TIER_COLORS[idx % TIER_COLORS.len()];
+            let width = px(if idx == 0 { 4.0 } else { 3.0 });
+            chart
+                .draw_series(LineSeries::new(
+                    vec![(tier.range / 1000.0, 0.0), (tier.range / 1000.0, chart_y_max * 0.8)],
+                    color.stroke_width(width),
+                ))?
+                .label(format!("{} {} ({:.1}km)", weapon.name, tier.name, tier.range / 1000.0))
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + px(10.0) as i32, y)], color.stroke_width(width)));
+        }
+
+        let target_distance_line_km: Vec<(f64, f64)> = target_distance_line
+            .iter()
+            .map(|(x, y)| (*x / 1000.0, *y))
+            .collect();
+    
+        chart
+            .draw_series(LineSeries::new(
+                target_distance_line_km,
+                RED.stroke_width(px(4.0)),
+            ))?
+            .label(target.label.as_str())
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + px(10.0) as i32, y)], RED.stroke_width(px(4.0))));
+
+        if i < animation_points.len() {
+            let (x, y) = animation_points[i];
+            let (x_km, y_km) = (x / 1000.0, y);
+        
+            chart
+                .draw_series(PointSeries::of_element(
+                    vec![(x_km, y_km)],
+                    px(12.0),
+                    &RED,
+                    &|c, s, st| EmptyElement::at(c) + Circle::new((0, 0), s, st.filled()),
+                ))?
+                .label("Projectile Position")
+                .legend(move |(x, y)| Circle::new((x + px(5.0) as i32, y), px(5.0), RED.filled()));
+        
+            if i > 5 {
+                let trail_start = i - 5;
+                let trail_points_km: Vec<(f64, f64)> = animation_points
+                    .iter()
+                    .skip(trail_start)
+                    .take(6)
+                    .map(|(x, y)| (*x / 1000.0, *y))
+                    .collect();
+            
+                chart.draw_series(LineSeries::new(
+                    trail_points_km,
+                    RED.mix(0.6).stroke_width(px(3.0)),
+                ))?;
+            }
+        }
+
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .label_font(("Arial", px(28.0)))
+            .draw()?;
+
+        legend_area.fill(&RGBColor(240, 240, 255))?;
+
+        let verdict_color = verdict_color(analysis.verdict());
+        let out_of_range = range_shortfall > 0.0;
+        let compact_info = vec![
+            (
+                format!(
+                    "{}-{} {} ANALYSIS",
+                    launch.country.to_uppercase(),
+                    target.country.to_uppercase(),
+                    weapon.name
+                ),
+                14,
+                BLACK,
+                true,
+            ),
+            (
+                format!(
+                    "Max Range: {:.0}km",
+                    weapon.max_range_operational / 1000.0
+                ),
+                13,
+                BLUE,
+                false,
+            ),
+            (
+                format!("Distance: {:.1}km", actual_distance / 1000.0),
+                13,
+                BLACK,
+                false,
+            ),
+            (
+                if out_of_range {
+                    format!("Shortfall: {:.1}km", range_shortfall / 1000.0)
+                } else {
+                    format!("Margin: {:.1}km", -range_shortfall / 1000.0)
+                },
+                13,
+                verdict_color,
+                false,
+            ),
+            (
+                format!("Distance / max range: {:.1}×", range_multiplier),
+                13,
+                verdict_color,
+                true,
+            ),
+            (
+                analysis.verdict_headline(),
+                13,
+                verdict_color,
+                true,
+            ),
+            (
+                format!(
+                    "Most generous tier: {} {:.1}km",
+                    analysis.most_generous_tier().name,
+                    analysis.most_generous_tier().range / 1000.0
+                ),
+                13,
+                MAGENTA,
+                false,
+            ),
+            (
+                "MAX RANGE vs ACTUAL DISTANCE:".to_string(),
+                13,
+                BLACK,
+                true,
+            ),
+            (
+                format!(
+                    "• {} max range: {:.0}km (ballistic limit)",
+                    weapon.name,
+                    weapon.max_range_operational / 1000.0
+                ),
+                13,
+                BLUE,
+                false,
+            ),
+            (
+                format!("• Required distance: {:.1}km (GPS measured)", actual_distance / 1000.0),
+                13,
+                BLUE,
+                false,
+            ),
+            (
+                "• Physics: Projectiles follow parabolic paths".to_string(),
+                13,
+                BLUE,
+                false,
+            ),
+            (
+                format!(
+                    "• Air drag cuts range to {:.1}km ({} Earth)",
+                    analysis.drag().range / 1000.0,
+                    analysis.earth_model.name()
+                ),
+                13,
+                BLUE,
+                false,
+            ),
+            (
+                if out_of_range {
+                    format!("• Gap: {:.1}km beyond the max range", range_shortfall / 1000.0)
+                } else {
+                    format!("• Within the max range by {:.1}km", -range_shortfall / 1000.0)
+                },
+                13,
+                verdict_color,
+                false,
+            ),
+            (
+                "📐 MATHEMATICAL CALCULATIONS:".to_string(),
+                13,
+                BLACK,
+                true,
+            ),
+            (
+                "##############################################".to_string(),
+                13,
+                BLACK,
+                false,
+            ),
+            (
+                "① Haversine Distance Formula:".to_string(),
+                13,
+                BLUE,
+                true,
+            ),
+            (
+                "d = 2R ⋅ arcsin(√(sin²(Δφ/2) + cos(φ₁)cos(φ₂)sin²(Δλ/2)))".to_string(),
+                13,
+                BLUE,
+                false,
+            ),
+            (
+                format!("Given: φ₁={:.4}°, λ₁={:.4}°, φ₂={:.7}°, λ₂={:.7}°", launch.lat, launch.lon, target.lat, target.lon),
+                13,
+                BLUE,
+                false,
+            ),
+            (
+                format!("Δφ = {:.4}°, Δλ = {:.4}°, R = 6,371km", target.lat - launch.lat, target.lon - launch.lon),
+                13,
+                BLUE,
+                false,
+            ),
+            (
+                format!("∴ d = {:.1}km (GPS verified)", actual_distance / 1000.0),
+                13,
+                BLUE,
+                true,
+            ),
+            (
+                "② Projectile Range Formula:".to_string(),
+                13,
+                BLUE,
+                true,
+            ),
+            (
+                "R = (v₀² ⋅ sin(2θ)) / g #FIND R".to_string(),
+                13,
+                BLUE,
+                false,
+            ),
+            (
+                "📋 CONSTANT DEFINITIONS:".to_string(),
+                13,
+                BLACK,
+                true,
+            ),
+            (
+                format!("• v₀ = {:.0} m/s (Initial muzzle velocity of {})", v0, weapon.name),
+                13,
+                BLUE,
+                false,
+            ),
+            (
+                format!("• θ = {:.0}° (Optimal launch angle for maximum range)", optimal_angle),
+                13,
+                BLUE,
+                false,
+            ),
+            (
+                format!("• g = {} m/s² (Earth's gravitational acceleration)", g),
+                13,
+                BLUE,
+                false,
+            ),
+            (
+                "• R = 6,371 km (Earth's mean radius for Haversine)".to_string(),
+                13,
+                BLUE,
+                false,
+            ),
+            (
+                "📊 Then: ".to_string(),
+                13,
+                BLACK,
+                true,
+            ),
+            (
+                format!("R = ({:.0}² ⋅ sin({:.0}°)) / {}", v0, 2.0 * optimal_angle, g),
+                13,
+                BLUE,
+                false,
+            ),
+            (
+                format!("R = {:.0} ⋅ {:.1} / {} = {:.1}km", v0.powi(2), (2.0 * theta).sin(), g, level_range / 1000.0),
+                13,
+                BLUE,
+                false,
+            ),
+            (
+                "③ Range Check:".to_string(),
+                13,
+                verdict_color,
+                true,
+            ),
+            (
+                format!("Required Distance / Max Range = {:.1}km / {:.0}km", actual_distance / 1000.0, weapon.max_range_operational / 1000.0),
+                13,
+                verdict_color,
+                false,
+            ),
+            (
+                format!(
+                    "Range Factor = {:.1}× ({})",
+                    range_multiplier,
+                    if out_of_range { "out of range" } else { "within range" }
+                ),
+                20,
+                verdict_color,
+                true,
+            ),
+        ];
+
+        for (idx, (text, font_size, color, bold)) in compact_info.iter().enumerate() {
+            let y_pos = scaled(38.0 + idx as f64 * 30.0, ls) as i32;
+            let font_size = scaled(*font_size as f64 * 1.5, ls);
+            let font_style = if *bold {
+                ("Arial", font_size)
+                    .into_font()
+                    .style(FontStyle::Bold)
+                    .color(color)
+            } else {
+                ("Arial", font_size).into_font().style(FontStyle::Normal).color(color)
+            };
+
+            legend_area.draw_text(text, &font_style, (scaled(15.0, ls) as i32, y_pos))?;
+        }
+
+        Ok(())
+    }
+}
+
+const HISTOGRAM_BINS: usize = 80;
+
+/// Histogram of Monte Carlo launch-target distances against the sampled
+/// operational ranges.
+pub fn histogram_chart(
+    scenario: &Scenario,
+    result: &MonteCarloResult,
+    path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let x_max = (result.distance.max.max(result.max_range_operational.max) * 1.05 / 1000.0).ceil();
+    let bin_width = x_max / HISTOGRAM_BINS as f64;
+    let bin_counts = |values: &[f64]| {
+        let mut bins = vec![0u32; HISTOGRAM_BINS];
+        for v in values {
+            let idx = ((v / 1000.0) / bin_width) as usize;
+            bins[idx.min(HISTOGRAM_BINS - 1)] += 1;
+        }
+        bins
+    };
+    let distance_bins = bin_counts(&result.distances);
+    let range_bins = bin_counts(&result.ranges);
+    let y_max = distance_bins.iter().chain(&range_bins).copied().max().unwrap_or(1) as f64 * 1.1;
+
+    let root = BitMapBackend::new(path, HISTOGRAM_SIZE).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption(
+            format!(
+                "{} Monte Carlo: P(within range) = {}",
+                scenario.weapon.name,
+                format_probability(result.probability_within_operational, result.samples)
+            ),
+            ("Arial", 48).into_font().style(FontStyle::Bold).color(&RED),
+        )
+        .margin(50)
+        .x_label_area_size(90)
+        .y_label_area_size(105)
+        .build_cartesian_2d(0f64..x_max, 0f64..y_max)?;
+
+    chart
+        .configure_mesh()
+        .x_desc("Distance (kilometers)")
+        .y_desc("Samples")
+        .axis_desc_style(("Arial", 42))
+        .label_style(("Arial", 18))
+        .draw()?;
+
+    let bars = |bins: Vec<u32>, color: RGBAColor| {
+        bins.into_iter().enumerate().filter(|(_, n)| *n > 0).map(move |(i, n)| {
+            let x0 = i as f64 * bin_width;
+            Rectangle::new([(x0, 0.0), (x0 + bin_width, n as f64)], color.filled())
+        })
+    };
+
+    chart
+        .draw_series(bars(range_bins, GREEN.mix(0.6)))?
+        .label(format!("Sampled {} max range", scenario.weapon.name))
+        .legend(|(x, y)| Rectangle::new([(x, y - 6), (x + 12, y + 6)], GREEN.mix(0.6).filled()));
+    chart
+        .draw_series(bars(distance_bins, RED.mix(0.6)))?
+        .label(format!(
+            "Launch-target distance (±{:.0}m / ±{:.0}m)",
+            scenario.launch.uncertainty, scenario.target.uncertainty
+        ))
+        .legend(|(x, y)| Rectangle::new([(x, y - 6), (x + 12, y + 6)], RED.mix(0.6).filled()));
+
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::UpperMiddle)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .label_font(("Arial", 28))
+        .draw()?;
+
+    root.present()?;
+    Ok(())
+}
+
+pub fn proof_card(
+    scenario: &Scenario,
+    analysis: &Analysis,
+) -> Result<core::Mat, Box<dyn std::error::Error>> {
+    let weapon = &scenario.weapon;
+    let launch = &scenario.launch;
+    let target = &scenario.target;
+    let layout = Layout::new(&scenario.output);
+    let (width, height) = (layout.width as i32, layout.height as i32);
+    let k = layout.card_scale();
+    let px = |value: f64| scaled(value, k) as i32;
+
+    let proof_lines = report::proof_lines(scenario, analysis);
+
+    let mut proof_img = core::Mat::new_rows_cols_with_default(
+        height,
+        width,
+        core::CV_8UC3,
+        core::Scalar::new(255.0, 255.0, 255.0, 0.0),
+    )?;
+
+    let verdict = analysis.verdict();
+    let RGBColor(r, g, b) = verdict_color(verdict);
+    let verdict_scalar = core::Scalar::new(b as f64, g as f64, r as f64, 0.0);
+
+    let banner = px(90.0);
+    imgproc::rectangle(
+        &mut proof_img,
+        core::Rect::new(0, 0, width, banner),
+        verdict_scalar,
+        -1,
+        imgproc::LINE_8,
+        0,
+    )?;
+
+    let title = format!(
+        "{} {} TO {}: {}",
+        launch.country.to_uppercase(),
+        weapon.name,
+        target.country.to_uppercase(),
+        analysis.verdict_headline()
+    );
+    let title_thickness = px(4.0);
+    let title_scale = fit_text(&title, 1.5 * k, title_thickness, width - px(90.0))?;
+    imgproc::put_text(
+        &mut proof_img,
+        &title,
+        core::Point::new(px(45.0), banner * 2 / 3),
+        imgproc::FONT_HERSHEY_SIMPLEX,
+        title_scale,
+        core::Scalar::new(255.0, 255.0, 255.0, 0.0),
+        title_thickness,
+        imgproc::LINE_8,
+        false,
+    )?;
+
+    // Two columns side by side, or one in portrait; the line pitch and
+    // text shrink until every line fits above the bottom margin.
+    let columns = if layout.portrait() { 1 } else { 2 };
+    let top = banner + px(45.0);
+    let bottom = height - px(50.0);
+    let rows = proof_lines.len().div_ceil(columns).max(1);
+    let pitch = (35.0 * k).min((bottom - top) as f64 / rows as f64);
+    let column_width = width / columns as i32;
+
+    if columns == 2 {
+        imgproc::line(
+            &mut proof_img,
+            core::Point::new(column_width, banner + px(10.0)),
+            core::Point::new(column_width, bottom),
+            core::Scalar::new(150.0, 150.0, 150.0, 0.0),
+            px(3.0),
+            imgproc::LINE_8,
+            0,
+        )?;
+    }
+
+    for (column, lines) in proof_lines.chunks(rows).enumerate() {
+        let x = column as i32 * column_width + px(30.0);
+        draw_proof_column(&mut proof_img, lines, x, top, pitch, column_width - px(60.0), verdict_scalar)?;
+    }
+
+    Ok(proof_img)
+}
+
+/// Largest Hershey font scale up to `scale` at which `text` fits `max_width`.
+fn fit_text(text: &str, scale: f64, thickness: i32, max_width: i32) -> Result<f64, Box<dyn std::error::Error>> {
+    let mut baseline = 0;
+    let size = imgproc::get_text_size(text, imgproc::FONT_HERSHEY_SIMPLEX, scale, thickness, &mut baseline)?;
+    if size.width > max_width && size.width > 0 {
+        Ok(scale * max_width as f64 / size.width as f64)
+    } else {
+        Ok(scale)
+    }
+}
+
+fn draw_proof_column(
+    proof_img: &mut core::Mat,
+    lines: &[String],
+    x: i32,
+    top: i32,
+    pitch: f64,
+    max_width: i32,
+    verdict_color: core::Scalar,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Text sizes below are tuned for a 35 px line pitch.
+    const VERDICT_TAGS: [&str; 6] =
+        ["[POSSIBLE]", "[EXTREME]", "[IMPLAUSIBLE]", "[IMPOSSIBLE]", "[RANGE CHECK]", "[CONCLUSION]"];
+    let k = pitch / 35.0;
+    let thickness = ((2.0 * k).round() as i32).max(1);
+    for (i, text) in lines.iter().enumerate() {
+        let y = top + (i as f64 * pitch) as i32;
+
+        let (font_scale, color) =
+            if VERDICT_TAGS.iter().any(|tag| text.starts_with(tag)) {
+                (0.8, verdict_color)
+            } else if text.contains("SPECIFICATIONS") || text.contains("VERIFICATION") {
+                (0.7, core::Scalar::new(200.0, 0.0, 0.0, 0.0))
+            } else if text.contains("═") {
+                (0.6, core::Scalar::new(100.0, 100.0, 100.0, 0.0))
+            } else if text.starts_with("•") {
+                (0.6, core::Scalar::new(0.0, 0.0, 0.0, 0.0))
+            } else {
+                (0.7, core::Scalar::new(0.0, 0.0, 0.0, 0.0))
+            };
+
+        imgproc::put_text(
+            proof_img,
+            text,
+            core::Point::new(x, y),
+            imgproc::FONT_HERSHEY_SIMPLEX,
+            fit_text(text, font_scale * k, thickness, max_width)?,
+            color,
+            thickness,
+            imgproc::LINE_8,
+            false,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Static side view for the HTML and PDF reports: vacuum and drag
+/// trajectories, declared range tiers and the target distance.
+pub fn trajectory_chart<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    scenario: &Scenario,
+    analysis: &Analysis,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    let x_max = analysis.actual_distance.max(analysis.most_generous_tier().range) * 1.05 / 1000.0;
+    let terrain = terrain_km(scenario, x_max);
+    let launch_altitude = analysis.altitudes.launch;
+    let ground = terrain.iter().map(|(_, h)| *h).fold(launch_altitude, f64::max);
+    let y_max = analysis.max_h.max(analysis.drag().max_height) * 1.2 + ground.max(0.0);
+    root.fill(&WHITE)?;
+
+    let mut chart = ChartBuilder::on(root)
+        .caption(
+            format!("{} trajectory vs target distance", scenario.weapon.name),
+            ("Arial", 32).into_font().style(FontStyle::Bold),
+        )
+        .margin(30)
+        .x_label_area_size(60)
+        .y_label_area_size(80)
+        .build_cartesian_2d(0f64..x_max, 0f64..y_max)?;
+
+    chart
+        .configure_mesh()
+        .x_desc("Distance (kilometers)")
+        .y_desc("Altitude (meters)")
+        .axis_desc_style(("Arial", 24))
+        .label_style(("Arial", 18))
+        .draw()?;
+
+    if !terrain.is_empty() {
+        chart
+            .draw_series(AreaSeries::new(terrain, 0.0, TERRAIN_COLOR.mix(0.6)).border_style(TERRAIN_COLOR.stroke_width(2)))?
+            .label("Terrain (SRTM)")
+            .legend(|(x, y)| Rectangle::new([(x, y - 5), (x + 10, y + 5)], TERRAIN_COLOR.mix(0.6).filled()));
+    }
+
+    let km = |points: Vec<(f64, f64)>| points.into_iter().map(|(x, y)| (x / 1000.0, y)).collect::<Vec<_>>();
+    let vacuum: Vec<(f64, f64)> = analysis
+        .trajectory
+        .sample_until(400, analysis.vacuum_impact.time)
+        .into_iter()
+        .map(|(x, y)| (x, y + launch_altitude))
+        .collect();
+    chart
+        .draw_series(LineSeries::new(km(vacuum), BLUE.stroke_width(3)))?
+        .label(format!("Vacuum ({:.1}km)", analysis.range_theoretical / 1000.0))
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], BLUE.stroke_width(3)));
+    chart
+        .draw_series(LineSeries::new(km(analysis.drag().sample(400)), CYAN.stroke_width(3)))?
+        .label(format!("With Air Drag ({:.1}km)", analysis.drag().range / 1000.0))
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], CYAN.stroke_width(3)));
+    let blocked = blocked_impacts_km(analysis);
+    if !blocked.is_empty() {
+        chart
+            .draw_series(blocked.into_iter().map(|point| Cross::new(point, 10, BLACK.stroke_width(3))))?
+            .label("Blocked by terrain")
+            .legend(|(x, y)| Cross::new((x + 5, y), 5, BLACK.stroke_width(2)));
+    }
+
+    let declared = analysis.tiers.iter().filter(|t| t.kind == TierKind::Declared);
+    for (idx, tier) in declared.enumerate() {
+        let color = TIER_COLORS[idx % TIER_COLORS.len()];
+        chart
+            .draw_series(LineSeries::new(
+                vec![(tier.range / 1000.0, 0.0), (tier.range / 1000.0, y_max * 0.8)],
+                color.stroke_width(3),
+            ))?
+            .label(format!("{} ({:.1}km)", tier.name, tier.range / 1000.0))
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], color.stroke_width(3)));
+    }
+
+    let target_km = analysis.actual_distance / 1000.0;
+    chart
+        .draw_series(LineSeries::new(
+            vec![(target_km, 0.0), (target_km, y_max * 0.8)],
+            RED.stroke_width(4),
+        ))?
+        .label(format!("{} ({:.1}km)", scenario.target.label, target_km))
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], RED.stroke_width(4)));
+
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::UpperRight)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .label_font(("Arial", 18))
+        .draw()?;
+
+    root.present()?;
+    Ok(())
+}
+
+/// Horizontal bars of every range tier against the target distance.
+pub fn tier_chart<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    analysis: &Analysis,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    let tiers = &analysis.tiers;
+    let x_max = analysis.actual_distance.max(analysis.most_generous_tier().range) * 1.1 / 1000.0;
+    let rows = tiers.len() as f64;
+    root.fill(&WHITE)?;
+
+    let mut chart = ChartBuilder::on(root)
+        .caption("Range tiers vs required distance", ("Arial", 32).into_font().style(FontStyle::Bold))
+        .margin(30)
+        .x_label_area_size(60)
+        .y_label_area_size(20)
+        .build_cartesian_2d(0f64..x_max, -0.5f64..rows - 0.5)?;
+
+    chart
+        .configure_mesh()
+        .disable_y_mesh()
+        .disable_y_axis()
+        .x_desc("Distance (kilometers)")
+        .axis_desc_style(("Arial", 24))
+        .label_style(("Arial", 18))
+        .draw()?;
+
+    for (idx, tier) in tiers.iter().enumerate() {
+        let y = rows - 1.0 - idx as f64;
+        let color = if tier.reaches() {
+            GREEN
+        } else if tier.kind == TierKind::Declared {
+            RGBColor(255, 140, 0)
+        } else {
+            BLUE
+        };
+        chart.draw_series(std::iter::once(Rectangle::new(
+            [(0.0, y - 0.3), (tier.range / 1000.0, y + 0.3)],
+            color.mix(0.7).filled(),
+        )))?;
+        chart.draw_series(std::iter::once(Text::new(
+            format!("{} {:.1}km", tier.name, tier.range / 1000.0),
+            (tier.range / 1000.0 + x_max * 0.01, y + 0.1),
+            ("Arial", 18).into_font(),
+        )))?;
+    }
+
+    let target_km = analysis.actual_distance / 1000.0;
+    chart
+        .draw_series(LineSeries::new(
+            vec![(target_km, -0.5), (target_km, rows - 0.5)],
+            RED.stroke_width(4),
+        ))?
+        .label(format!("Required distance ({:.1}km)", target_km))
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], RED.stroke_width(4)));
+
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::LowerRight)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .label_font(("Arial", 18))
+        .draw()?;
+
+    root.present()?;
+    Ok(())
+}
+
+/// Writes the map view as SVG or, for any other extension, PNG.
+pub fn map_image(scenario: &Scenario, analysis: &Analysis, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let size = (scenario.output.width, scenario.output.height);
+    let svg = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("svg"));
+    if svg {
+        map_chart(&SVGBackend::new(path, size).into_drawing_area(), scenario, analysis)
+    } else {
+        map_chart(&BitMapBackend::new(path, size).into_drawing_area(), scenario, analysis)
+    }
+}
+
+/// Plan view around the launch site: range rings for every tier, the
+/// great-circle path to the target and a latitude/longitude graticule.
+pub fn map_chart<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    scenario: &Scenario,
+    analysis: &Analysis,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    let launch = &scenario.launch;
+    let target = &scenario.target;
+    let projection = MapProjection::new(scenario.map.projection, launch.lat, launch.lon);
+    let km = |(lat, lon): (f64, f64)| {
+        let (x, y) = projection.project(lat, lon);
+        (x / 1000.0, y / 1000.0)
+    };
+
+    let rings: Vec<Vec<(f64, f64)>> = analysis
+        .tiers
+        .iter()
+        .map(|tier| map::range_ring(launch.lat, launch.lon, tier.range, 180))
+        .collect();
+    let path = map::great_circle(launch.lat, launch.lon, target.lat, target.lon, 100);
+
+    // Extent of everything drawn, padded and widened to the drawing area's
+    // aspect ratio so kilometers are square.
+    let geographic: Vec<(f64, f64)> = rings.iter().flatten().chain(&path).copied().collect();
+    let projected: Vec<(f64, f64)> = geographic.iter().map(|p| km(*p)).collect();
+    let (mut x_min, mut x_max, mut y_min, mut y_max) = projected.iter().fold(
+        (f64::MAX, f64::MIN, f64::MAX, f64::MIN),
+        |(x0, x1, y0, y1), (x, y)| (x0.min(*x), x1.max(*x), y0.min(*y), y1.max(*y)),
+    );
+    let pad = 0.08 * (x_max - x_min).max(y_max - y_min);
+    (x_min, x_max, y_min, y_max) = (x_min - pad, x_max + pad, y_min - pad, y_max + pad);
+
+    let (width, height) = root.dim_in_pixel();
+    let s = width.min(height) as f64 / 1080.0;
+    let px = move |value: f64| scaled(value, s);
+    let (margin, x_label, y_label, caption) = (px(30.0), px(70.0), px(90.0), px(48.0));
+    let plot_width = width.saturating_sub(2 * margin + y_label).max(1) as f64;
+    let plot_height = height.saturating_sub(2 * margin + x_label + caption * 3 / 2).max(1) as f64;
+    let aspect = plot_width / plot_height;
+    if (x_max - x_min) / (y_max - y_min) < aspect {
+        let grow = ((y_max - y_min) * aspect - (x_max - x_min)) / 2.0;
+        (x_min, x_max) = (x_min - grow, x_max + grow);
+    } else {
+        let grow = ((x_max - x_min) / aspect - (y_max - y_min)) / 2.0;
+        (y_min, y_max) = (y_min - grow, y_max + grow);
+    }
+
+    let title = format!(
+        "{} range rings around {} ({})",
+        scenario.weapon.name,
+        launch.label,
+        scenario.map.projection.name()
+    );
+    // Shrink long titles to fit narrow frames, at about 0.6em per glyph.
+    let title_size = caption.min((width as f64 * 1.6 / title.chars().count() as f64) as u32);
+
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(root)
+        .caption(title, ("Arial", title_size).into_font().style(FontStyle::Bold))
+        .margin(margin)
+        .x_label_area_size(x_label)
+        .y_label_area_size(y_label)
+        .build_cartesian_2d(x_min..x_max, y_min..y_max)?;
+
+    chart
+        .configure_mesh()
+        .disable_mesh()
+        .x_desc("Kilometers east of launch")
+        .y_desc("Kilometers north of launch")
+        .axis_desc_style(("Arial", px(28.0)))
+        .label_style(("Arial", px(18.0)))
+        .draw()?;
+
+    // Basemap tiles under everything else, credited in the corner.
+    let attribution = match &scenario.map.basemap {
+        Some(options) => {
+            let mut basemap = Basemap::open(options)?;
+            let (plot_width, plot_height) = chart.plotting_area().dim_in_pixel();
+            let image = basemap.render(
+                &projection,
+                x_min * 1000.0..x_max * 1000.0,
+                y_min * 1000.0..y_max * 1000.0,
+                plot_width,
+                plot_height,
+            )?;
+            let element = BitMapElement::with_owned_buffer((x_min, y_max), (plot_width, plot_height), image)
+                .ok_or("basemap image smaller than the plot")?;
+            chart.draw_series(std::iter::once(element))?;
+            Some(basemap.attribution)
+        }
+        None => None,
+    };
+
+    // Graticule over the latitude/longitude span of the view, estimated
+    // from its extent in kilometers; lines are clipped to the view anyway.
+    let (lat_min, lat_max, lon_min, lon_max) = geographic.iter().fold(
+        (f64::MAX, f64::MIN, f64::MAX, f64::MIN),
+        |(a0, a1, o0, o1), (lat, lon)| (a0.min(*lat), a1.max(*lat), o0.min(*lon), o1.max(*lon)),
+    );
+    let step = map::graticule_step((lat_max - lat_min).max(lon_max - lon_min));
+    let km_per_degree = EARTH_RADIUS.to_radians() / 1000.0;
+    let km_per_degree_lon = km_per_degree * launch.lat.to_radians().cos().max(0.01);
+    let lat_min = lat_min.min(launch.lat + y_min / km_per_degree);
+    let lat_max = lat_max.max(launch.lat + y_max / km_per_degree);
+    let lon_min = lon_min.min(launch.lon + x_min / km_per_degree_lon);
+    let lon_max = lon_max.max(launch.lon + x_max / km_per_degree_lon);
+    let (lat_min, lat_max) = ((lat_min / step).floor() * step - step, (lat_max / step).ceil() * step + step);
+    let (lon_min, lon_max) = ((lon_min / step).floor() * step - step, (lon_max / step).ceil() * step + step);
+    let grid_style = RGBColor(200, 200, 200).stroke_width(1);
+    let label_style = ("Arial", px(16.0)).into_font().color(&RGBColor(120, 120, 120));
+    let in_view = |(x, y): &(f64, f64)| *x >= x_min && *x <= x_max && *y >= y_min && *y <= y_max;
+    let lines = ((lat_max - lat_min) / step).round() as usize;
+    for i in 0..=lines {
+        let lat = lat_min + i as f64 * step;
+        let line: Vec<(f64, f64)> = (0..=60)
+            .map(|j| km((lat, lon_min + (lon_max - lon_min) * j as f64 / 60.0)))
+            .collect();
+        if let Some(&(x, y)) = line.iter().find(|p| in_view(p)) {
+            chart.draw_series(std::iter::once(Text::new(
+                format!("{:.2}°{}", lat.abs(), if lat >= 0.0 { 'N' } else { 'S' }),
+                (x, y),
+                label_style.clone(),
+            )))?;
+        }
+        chart.draw_series(LineSeries::new(line.into_iter().filter(|p| in_view(p)), grid_style))?;
+    }
+    let lines = ((lon_max - lon_min) / step).round() as usize;
+    for i in 0..=lines {
+        let lon = lon_min + i as f64 * step;
+        let line: Vec<(f64, f64)> = (0..=60)
+            .map(|j| km((lat_min + (lat_max - lat_min) * j as f64 / 60.0, lon)))
+            .collect();
+        if let Some(&(x, y)) = line.iter().find(|p| in_view(p)) {
+            chart.draw_series(std::iter::once(Text::new(
+                format!("{:.2}°{}", lon.abs(), if lon >= 0.0 { 'E' } else { 'W' }),
+                (x, y),
+                label_style.clone(),
+            )))?;
+        }
+        chart.draw_series(LineSeries::new(line.into_iter().filter(|p| in_view(p)), grid_style))?;
+    }
+
+    let mut declared = 0;
+    for (tier, ring) in analysis.tiers.iter().zip(&rings) {
+        let ring: Vec<(f64, f64)> = ring.iter().map(|p| km(*p)).collect();
+        let (color, width) = match tier.kind {
+            TierKind::Declared => {
+                declared += 1;
+                (TIER_COLORS[(declared - 1) % TIER_COLORS.len()], px(4.0))
+            }
+            TierKind::Physics => (RGBColor(110, 110, 110), px(2.0)),
+        };
+        let label = format!("{} ({:.1}km)", tier.name, tier.range / 1000.0);
+        match tier.kind {
+            TierKind::Declared => chart.draw_series(LineSeries::new(ring.clone(), color.stroke_width(width)))?,
+            TierKind::Physics => chart.draw_series(DashedLineSeries::new(
+                ring.clone(),
+                px(12.0),
+                px(8.0),
+                color.stroke_width(width),
+            ))?,
+        }
+        .label(label.clone())
+        .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + px(20.0) as i32, y)], color.stroke_width(width)));
+        // Label just above the northernmost point of the ring.
+        let style = ("Arial", px(18.0))
+            .into_font()
+            .color(&color)
+            .pos(Pos::new(HPos::Center, VPos::Bottom));
+        chart.draw_series(std::iter::once(
+            EmptyElement::at(ring[0]) + Text::new(label, (0, -(px(6.0) as i32)), style),
+        ))?;
+    }
+
+    chart
+        .draw_series(LineSeries::new(path.iter().map(|p| km(*p)), RED.stroke_width(px(3.0))))?
+        .label(format!("Great circle to target ({:.1}km)", analysis.actual_distance / 1000.0))
+        .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + px(20.0) as i32, y)], RED.stroke_width(px(3.0))));
+
+    let marker = px(10.0) as i32;
+    let launch_point = km((launch.lat, launch.lon));
+    let target_point = km((target.lat, target.lon));
+    chart
+        .draw_series(std::iter::once(TriangleMarker::new(launch_point, marker, BLUE.filled())))?
+        .label(format!("Launch: {}", launch.label))
+        .legend(move |(x, y)| TriangleMarker::new((x + px(10.0) as i32, y), px(6.0) as i32, BLUE.filled()));
+    chart
+        .draw_series(std::iter::once(Circle::new(target_point, marker, RED.filled())))?
+        .label(format!("Target: {}", target.label))
+        .legend(move |(x, y)| Circle::new((x + px(10.0) as i32, y), px(6.0), RED.filled()));
+    // Labels point away from the middle of the map so they stay inside it.
+    for (point, text) in [(launch_point, &launch.label), (target_point, &target.label)] {
+        let right = point.0 <= (x_min + x_max) / 2.0;
+        let (anchor, dx) = if right { (HPos::Left, marker + 4) } else { (HPos::Right, -marker - 4) };
+        let style = ("Arial", px(22.0)).into_font().color(&BLACK).pos(Pos::new(anchor, VPos::Bottom));
+        chart.draw_series(std::iter::once(
+            EmptyElement::at(point) + Text::new(text.clone(), (dx, -marker - 4), style),
+        ))?;
+    }
+
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::UpperLeft)
+        .background_style(WHITE.mix(0.85))
+        .border_style(BLACK)
+        .label_font(("Arial", px(20.0)))
+        .draw()?;
+
+    if let Some(attribution) = attribution {
+        let (x_range, y_range) = chart.plotting_area().get_pixel_range();
+        let style = ("Arial", px(16.0)).into_font().color(&BLACK);
+        let (text_width, text_height) = root.estimate_text_size(&attribution, &style)?;
+        let pad = px(4.0) as i32;
+        let (right, bottom) = (x_range.end, y_range.end);
+        let left = right - text_width as i32 - 2 * pad;
+        let top = bottom - text_height as i32 - 2 * pad;
+        root.draw(&Rectangle::new([(left, top), (right, bottom)], WHITE.mix(0.8).filled()))?;
+        root.draw(&Text::new(attribution, (left + pad, top + pad), style))?;
+    }
+
+    root.present()?;
+    Ok(())
+}
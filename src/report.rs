@@ -0,0 +1,425 @@
+use crate::analysis::{Analysis, Verdict};
+use crate::geo::compass_point;
+use crate::monte_carlo::{Percentiles, format_probability};
+use crate::scenario::{Scenario, Site};
+use crate::weapon::UNSOURCED;
+
+/// References for the constants built into the models, keyed as cited in
+/// the report.
+pub const MODEL_REFERENCES: [(&str, &str); 5] = [
+    ("iugg", "IUGG mean Earth radius, 6,371 km"),
+    ("wgs84", "World Geodetic System 1984, NIMA TR8350.2"),
+    ("icao", "ICAO Standard Atmosphere, Doc 7488"),
+    ("srtm", "NASA Shuttle Radar Topography Mission elevation model, 1 and 3 arc-second"),
+    ("scenario", "Value set in the scenario file"),
+];
+
+/// `lat, lon` with hemisphere suffixes, e.g. `33.900000S, 18.400000E`.
+pub fn lat_lon(lat: f64, lon: f64) -> String {
+    format!(
+        "{:.6}{}, {:.6}{}",
+        lat.abs(),
+        if lat >= 0.0 { 'N' } else { 'S' },
+        lon.abs(),
+        if lon >= 0.0 { 'E' } else { 'W' }
+    )
+}
+
+fn site_source(site: &Site) -> &str {
+    site.source.as_deref().unwrap_or(UNSOURCED)
+}
+
+pub fn proof_lines(scenario: &Scenario, analysis: &Analysis) -> Vec<String> {
+    let weapon = &scenario.weapon;
+    let launch = &scenario.launch;
+    let target = &scenario.target;
+    let actual_distance = analysis.actual_distance;
+    let v0 = weapon.muzzle_velocity;
+    let optimal_angle = scenario.physics.launch_angle;
+    let range_theoretical = analysis.range_theoretical;
+    let t_flight = analysis.t_flight;
+    let max_h = analysis.max_h;
+    let drop = analysis.trajectory.drop();
+    let range_shortfall = analysis.range_shortfall;
+    let range_multiplier = analysis.range_multiplier;
+    let sea_level = scenario.atmosphere.at(0.0);
+    let apex = scenario.atmosphere.at(analysis.altitudes.launch + analysis.drag().max_height);
+    let cite = |field: &str| format!("[{}]", weapon.source(field));
+
+    let mut lines = vec![
+        format!(
+            "ANALYSIS: {} from {} vs {} ATTACK CLAIM",
+            weapon.name,
+            launch.country.to_uppercase(),
+            target.country.to_uppercase()
+        ),
+        "================================================================".to_string(),
+        "".to_string(),
+        format!("OFFICIAL {} SPECIFICATIONS:", weapon.name.to_uppercase()),
+        format!("* System: {}", weapon.description),
+        format!("* Caliber: {:.0}mm {}", weapon.calibre, cite("calibre")),
+        format!("* Projectile Mass: {:.1} kg {}", weapon.projectile_mass, cite("projectile_mass")),
+        match weapon.warhead_mass {
+            Some(mass) => format!("* Warhead Mass: {:.1} kg HE-FRAG {}", mass, cite("warhead_mass")),
+            None => "* Warhead Mass: not catalogued".to_string(),
+        },
+        match weapon.projectile_length {
+            Some(length) => format!("* Length: {:.2} meters {}", length, cite("projectile_length")),
+            None => "* Length: not catalogued".to_string(),
+        },
+        format!(
+            "* Maximum Range (45 deg optimal): {:.1} km {}",
+            weapon.max_range_45deg / 1000.0,
+            cite("max_range_45deg")
+        ),
+        format!(
+            "* Operational Range (typical): {:.1} km {}",
+            weapon.max_range_operational / 1000.0,
+            cite("max_range_operational")
+        ),
+        "".to_string(),
+        "GEOGRAPHIC DISTANCE VERIFICATION:".to_string(),
+        format!(
+            "* Launch Coordinates: {} ({}) [{}]",
+            lat_lon(launch.lat, launch.lon),
+            launch.country,
+            site_source(launch)
+        ),
+        format!(
+            "* Target Coordinates: {} ({}) [{}]",
+            lat_lon(target.lat, target.lon),
+            target.country,
+            site_source(target)
+        ),
+        match &scenario.terrain_profile {
+            Some(profile) => {
+                let (distance, highest) = profile.highest();
+                format!(
+                    "* Elevations: launch {:.0} m, target {:.0} m, terrain peak {:.0} m at {:.1} km [srtm]",
+                    analysis.altitudes.launch,
+                    analysis.altitudes.impact,
+                    highest,
+                    distance / 1000.0
+                )
+            }
+            None => format!(
+                "* Elevations: launch {:.0} m, target {:.0} m above sea level [scenario]",
+                analysis.altitudes.launch, analysis.altitudes.impact
+            ),
+        },
+        format!("* Haversine Distance: {:.3} km (sphere R = 6,371 km) [iugg]", actual_distance / 1000.0),
+        format!(
+            "* Initial Bearing: {:.2} deg ({}), Final Bearing: {:.2} deg ({})",
+            analysis.initial_bearing,
+            compass_point(analysis.initial_bearing),
+            analysis.final_bearing,
+            compass_point(analysis.final_bearing)
+        ),
+        format!("* Path Midpoint: {}", lat_lon(analysis.midpoint.0, analysis.midpoint.1)),
+        format!(
+            "* WGS84 Geodesic Distance: {:.3} km ({}) [wgs84]",
+            analysis.ellipsoidal.distance / 1000.0,
+            if analysis.ellipsoidal.converged { "Vincenty" } else { "spherical fallback" }
+        ),
+        format!(
+            "* Spherical vs Ellipsoidal Difference: {:+.1} m",
+            analysis.ellipsoidal_difference()
+        ),
+        format!(
+            "* WGS84 Azimuths: {:.2} deg forward, {:.2} deg back",
+            analysis.ellipsoidal.forward_azimuth, analysis.ellipsoidal.back_azimuth
+        ),
+        format!("* GPS Verification: CONFIRMED"),
+        "".to_string(),
+        "BALLISTIC PHYSICS CALCULATIONS:".to_string(),
+        "* Theoretical Max Range Formula: R = v0 cos(theta) x (v0 sin(theta) + sqrt((v0 sin(theta))^2 + 2 g dh)) / g"
+            .to_string(),
+        "  (dh = launch minus target altitude; R = (v0^2 x sin(2*theta)) / g when dh = 0)".to_string(),
+        format!("* Initial Velocity: {:.1} m/s {}", v0, cite("muzzle_velocity")),
+        format!("* Optimal Launch Angle: {:.0} degrees [scenario]", optimal_angle),
+        if drop == 0.0 {
+            "* Altitude Difference: none, launch and target at the same altitude".to_string()
+        } else {
+            format!(
+                "* Altitude Difference: dh = {:+.0} m, launch {} the target",
+                drop,
+                if drop > 0.0 { "above" } else { "below" }
+            )
+        },
+        if drop == 0.0 {
+            format!("* Calculated Range: {:.3} km", range_theoretical / 1000.0)
+        } else if analysis.trajectory.reaches_impact_altitude() {
+            format!(
+                "* Calculated Range: {:.3} km ({:.3} km on level ground, {:+.0} m from dh)",
+                range_theoretical / 1000.0,
+                analysis.trajectory.level_range() / 1000.0,
+                analysis.altitude_difference()
+            )
+        } else {
+            format!(
+                "* Calculated Range: {:.3} km to the apex, which is below the target altitude",
+                range_theoretical / 1000.0
+            )
+        },
+        format!("* Flight Time: {:.1} seconds", t_flight),
+        format!("* Maximum Height: {:.0} meters above the launch site", max_h),
+        "".to_string(),
+        format!("VACUUM vs AIR DRAG (RK4, Mach-dependent Cd {}):", cite("drag_coefficients")),
+        format!(
+            "* Range: {:.3} km vacuum vs {:.3} km with drag ({:.0}% lost)",
+            range_theoretical / 1000.0,
+            analysis.drag().range / 1000.0,
+            analysis.drag_range_loss() * 100.0
+        ),
+        format!(
+            "* Flight Time: {:.1} s vacuum vs {:.1} s with drag",
+            t_flight, analysis.drag().flight_time
+        ),
+        format!(
+            "* Maximum Height: {:.0} m vacuum vs {:.0} m with drag",
+            max_h, analysis.drag().max_height
+        ),
+        format!("* Impact Speed with drag: {:.0} m/s", analysis.drag().impact_speed),
+        format!(
+            "* Flat vs Round Earth: {:.3} km vs {:.3} km ground arc ({:+.0} m)",
+            analysis.drag_flat.range / 1000.0,
+            analysis.drag_spherical.range / 1000.0,
+            analysis.curvature_difference()
+        ),
+    ];
+    let terrain_source = if scenario.terrain_profile.is_some() { "srtm" } else { "scenario" };
+    for (name, impact) in [("Vacuum", analysis.vacuum_impact), ("Drag", analysis.drag().impact())] {
+        lines.push(format!(
+            "* {} path meets the ground: {:.3} km out at {:.0} m elevation after {:.1} s [{}]",
+            name,
+            impact.range / 1000.0,
+            impact.elevation,
+            impact.time,
+            terrain_source
+        ));
+        if let Some(unblocked) = impact.unblocked_range {
+            lines.push(format!(
+                "  BLOCKED by a ridge short of the {:.3} km it would otherwise reach",
+                unblocked / 1000.0
+            ));
+        }
+    }
+    if let (Some(motor), Some(burnout)) = (weapon.motor, analysis.drag().burnout) {
+        lines.extend([
+            "".to_string(),
+            "BOOST PHASE (constant thrust and mass flow):".to_string(),
+            format!(
+                "* Motor: {:.1} kN {} for {:.2} s {}, {:.2} kg propellant {}",
+                motor.thrust / 1000.0,
+                cite("thrust"),
+                motor.burn_time,
+                cite("burn_time"),
+                motor.propellant_mass,
+                cite("propellant_mass")
+            ),
+            format!("* Specific Impulse: {:.0} s", motor.specific_impulse()),
+            format!(
+                "* Burnout: {:.0} m/s after {:.2} s, {:.0} m out and {:.0} m above the launch site",
+                burnout.speed,
+                burnout.time,
+                burnout.range,
+                burnout.altitude - analysis.altitudes.launch
+            ),
+            format!("* Published burnout velocity: {:.0} m/s {}", v0, cite("muzzle_velocity")),
+        ]);
+    }
+    lines.extend([
+        "".to_string(),
+        "MODEL ASSUMPTIONS:".to_string(),
+        format!(
+            "* Atmosphere: ISA [icao], sea level {:.2} K / {:.0} Pa [scenario] / {:.3} kg/m3",
+            scenario.atmosphere.sea_level_temperature,
+            scenario.atmosphere.sea_level_pressure,
+            sea_level.density
+        ),
+        format!(
+            "* Air at drag apex ({:.0} m): {:.3} kg/m3 ({:.0}% of sea level), sound {:.0} m/s",
+            analysis.drag().max_height,
+            apex.density,
+            apex.density / sea_level.density * 100.0,
+            apex.speed_of_sound
+        ),
+        format!(
+            "* Gravity: {} m/s2 [scenario], {} Earth (R = 6,371 km) [iugg], no wind",
+            scenario.physics.gravity,
+            analysis.earth_model.name()
+        ),
+        "".to_string(),
+        "RANGE ANALYSIS - MATHEMATICAL EVIDENCE:".to_string(),
+        format!("* Required Distance: {:.1} km", actual_distance / 1000.0),
+        format!(
+            "* Maximum {} Range: {:.1} km {}",
+            weapon.name,
+            weapon.max_range_operational / 1000.0,
+            cite("max_range_operational")
+        ),
+        format!("* Range Factor: {:.1}x the maximum range", range_multiplier),
+    ]);
+    if range_shortfall > 0.0 {
+        lines.extend([
+            format!("* Range Deficit: {:.1} km", range_shortfall / 1000.0),
+            format!(
+                "* Excess: {:.0}% beyond the maximum range",
+                (range_shortfall / weapon.max_range_operational) * 100.0
+            ),
+        ]);
+    } else {
+        lines.push(format!("* Range Margin: {:.1} km to spare", -range_shortfall / 1000.0));
+    }
+    lines.extend([
+        "".to_string(),
+        "TIERED VERDICT (every declared range and the physics bounds):".to_string(),
+        format!(
+            "{:<44} {:>9} {:>10} {:>7}  {}",
+            "Tier", "Range", "Shortfall", "Factor", "Result"
+        ),
+    ]);
+    for tier in &analysis.tiers {
+        lines.push(format!(
+            "{:<44} {:>6.1} km {:>7.1} km {:>6.1}x  {}",
+            format!("{} [{}]", tier.name, tier.source),
+            tier.range / 1000.0,
+            tier.shortfall.max(0.0) / 1000.0,
+            tier.multiplier,
+            match (tier.reaches(), tier.in_verdict) {
+                (true, true) => "REACHES",
+                (false, true) => "OUT OF RANGE",
+                (true, false) => "REACHES (not counted)",
+                (false, false) => "OUT OF RANGE (not counted)",
+            }
+        ));
+    }
+    for tier in analysis.tiers.iter().filter(|t| !t.in_verdict) {
+        let inputs = weapon.unsourced_drag_inputs();
+        lines.push(format!(
+            "* Not counted in the verdict: {}, its {} {} unsourced",
+            tier.name,
+            inputs.join(" and "),
+            if inputs.len() == 1 { "is" } else { "are" }
+        ));
+    }
+    let generous = analysis.most_generous_tier();
+    let verdict = analysis.verdict();
+    let first_reaching = analysis.deciding_tier().map(|t| t.name.as_str()).unwrap_or_default();
+    lines.extend([
+        format!(
+            "* Most generous tier: {} at {:.1} km, target {}",
+            generous.name,
+            generous.range / 1000.0,
+            if generous.reaches() {
+                "within reach".to_string()
+            } else {
+                format!("still {:.1} km out of range", generous.shortfall / 1000.0)
+            }
+        ),
+    ]);
+    lines.push("".to_string());
+    let km = |p: &Percentiles| {
+        format!(
+            "{:.1} / {:.1} / {:.1} km (5th / median / 95th)",
+            p.p05 / 1000.0,
+            p.p50 / 1000.0,
+            p.p95 / 1000.0
+        )
+    };
+    match &analysis.monte_carlo {
+        Some(mc) => lines.extend([
+            format!("UNCERTAINTY (Monte Carlo, {} samples, seed {}):", mc.samples, mc.seed),
+            format!(
+                "* Sites: launch ±{:.0} m, target ±{:.0} m",
+                launch.uncertainty, target.uncertainty
+            ),
+            format!("* Distance: {}", km(&mc.distance)),
+            format!("* Operational range: {}", km(&mc.max_range_operational)),
+            format!("* Vacuum range bound: {}", km(&mc.vacuum_range)),
+            format!(
+                "* P(distance <= operational range): {}",
+                format_probability(mc.probability_within_operational, mc.samples)
+            ),
+            format!(
+                "* P(distance <= vacuum range): {}",
+                format_probability(mc.probability_within_vacuum, mc.samples)
+            ),
+        ]),
+        None => lines.extend([
+            "UNCERTAINTY:".to_string(),
+            "* None given in the scenario; the verdict is deterministic".to_string(),
+        ]),
+    }
+    lines.extend([
+        "".to_string(),
+        "MILITARY EXPERT CONCLUSIONS:".to_string(),
+        format!("[VERIFIED] {} specifications cited per figure, see SOURCES", weapon.name),
+        "[VERIFIED] Geographic coordinates verified via satellite data".to_string(),
+        "[VERIFIED] Physics calculations conform to NATO ballistic standards".to_string(),
+        if range_shortfall > 0.0 {
+            format!("[VERIFIED] Range deficit: {:.1} km beyond the operational range", range_shortfall / 1000.0)
+        } else {
+            format!("[VERIFIED] Range margin: {:.1} km within the operational range", -range_shortfall / 1000.0)
+        },
+        "".to_string(),
+        "FINAL VERDICT:".to_string(),
+        match verdict {
+            Verdict::WithinOperational => "[POSSIBLE] CLAIM STATUS: THEORETICALLY POSSIBLE".to_string(),
+            Verdict::WithinDeclared => "[EXTREME] CLAIM STATUS: ONLY AT EXTREME DECLARED RANGE".to_string(),
+            Verdict::PhysicsOnly => "[IMPLAUSIBLE] CLAIM STATUS: BEYOND EVERY DECLARED RANGE".to_string(),
+            Verdict::Impossible => "[IMPOSSIBLE] CLAIM STATUS: PHYSICALLY IMPOSSIBLE".to_string(),
+        },
+        format!("[RANGE CHECK] {}", analysis.verdict_headline()),
+        match verdict {
+            Verdict::WithinOperational => "[CONCLUSION] ATTACK WITHIN THEORETICAL RANGE".to_string(),
+            Verdict::WithinDeclared => format!(
+                "[CONCLUSION] ATTACK NEEDS {} BEYOND OPERATIONAL RANGE",
+                first_reaching.to_uppercase()
+            ),
+            Verdict::PhysicsOnly => "[CONCLUSION] ATTACK EXCEEDS ALL PUBLISHED CAPABILITY".to_string(),
+            Verdict::Impossible => "[CONCLUSION] ATTACK IMPOSSIBLE FROM THIS DISTANCE".to_string(),
+        },
+        "".to_string(),
+    ]);
+    match verdict {
+        Verdict::Impossible => {
+            lines.extend([
+                "With the cited specifications and the given coordinates, even the".to_string(),
+                format!(
+                    "vacuum upper bound leaves {}'s {} short of {}.",
+                    launch.country, weapon.name, target.country
+                ),
+            ]);
+            lines.push(match &analysis.monte_carlo {
+                Some(mc) => format!(
+                    "Within the sampled uncertainties, P(distance <= vacuum range): {}.",
+                    format_probability(mc.probability_within_vacuum, mc.samples)
+                ),
+                None => "No uncertainty was given, so this verdict is deterministic.".to_string(),
+            });
+        }
+        Verdict::PhysicsOnly => lines.extend([
+            format!("No declared range of {}'s {} reaches {};", launch.country, weapon.name, target.country),
+            format!("only the {} model does.", first_reaching),
+        ]),
+        Verdict::WithinOperational | Verdict::WithinDeclared => lines.extend([
+            format!(
+                "{} is within the {} tier of {}'s {},",
+                target.label, first_reaching, launch.country, weapon.name
+            ),
+            "so range alone does not rule the claim out.".to_string(),
+        ]),
+    }
+    lines.extend(["".to_string(), "SOURCES:".to_string()]);
+    for (key, citation) in &weapon.references {
+        lines.push(format!("[{}] {}", key, citation));
+    }
+    for input in weapon.unsourced_drag_inputs() {
+        lines.push(format!("[{}] Estimated {}, no published source", UNSOURCED, input));
+    }
+    for (key, citation) in MODEL_REFERENCES {
+        lines.push(format!("[{}] {}", key, citation));
+    }
+    lines
+}
@@ -0,0 +1,313 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::analysis::{Analysis, RangeTier, Verdict};
+use crate::atmosphere::Atmosphere;
+use crate::ballistics;
+use crate::geo::EARTH_RADIUS;
+use crate::monte_carlo::MonteCarloResult;
+use crate::scenario::{EarthModel, Scenario, Site};
+
+/// Version of the results layout, see `schema/results.schema.json`. Bumped
+/// when a field is renamed, removed or changes meaning; new fields keep it.
+pub const SCHEMA_VERSION: u32 = 3;
+
+/// Everything one analysis run computed, in the shape written to JSON.
+/// Lengths are in meters, angles in degrees, times in seconds.
+#[derive(Debug, Clone, Serialize)]
+pub struct Results {
+    pub schema_version: u32,
+    pub tool: Tool,
+    pub inputs: Inputs,
+    pub distances: Distances,
+    pub bearings: Bearings,
+    pub ranges: Ranges,
+    /// Distance minus the operational range.
+    pub shortfall_m: f64,
+    /// Distance divided by the operational range.
+    pub multiplier: f64,
+    pub tiers: Vec<RangeTier>,
+    pub verdict: Verdict,
+    /// Sampled uncertainties; `None` when the scenario has none and the
+    /// verdict is deterministic.
+    pub monte_carlo: Option<MonteCarloResult>,
+    pub assumptions: Assumptions,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Tool {
+    pub name: &'static str,
+    pub version: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Inputs {
+    pub launch: SiteInput,
+    pub target: SiteInput,
+    pub weapon: WeaponInput,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SiteInput {
+    pub label: String,
+    pub country: String,
+    pub lat: f64,
+    pub lon: f64,
+    pub uncertainty_m: f64,
+    /// Ground height above sea level, when given or read from SRTM.
+    pub elevation_m: Option<f64>,
+    pub source: Option<String>,
+}
+
+impl SiteInput {
+    fn new(site: &Site) -> Self {
+        SiteInput {
+            label: site.label.clone(),
+            country: site.country.clone(),
+            lat: site.lat,
+            lon: site.lon,
+            uncertainty_m: site.uncertainty,
+            elevation_m: site.elevation,
+            source: site.source.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WeaponInput {
+    pub id: String,
+    pub name: String,
+    pub category: String,
+    pub max_range_operational_m: f64,
+    pub max_range_45deg_m: f64,
+    pub extended_ranges: Vec<ExtendedRangeInput>,
+    pub projectile_mass_kg: f64,
+    pub warhead_mass_kg: Option<f64>,
+    pub projectile_length_m: Option<f64>,
+    pub calibre_mm: f64,
+    pub muzzle_velocity_m_s: f64,
+    /// Rocket motor, when the catalog entry models a boost phase.
+    pub motor: Option<MotorInput>,
+    /// Source key per catalog field.
+    pub sources: BTreeMap<String, String>,
+    /// Full citation per source key.
+    pub references: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct MotorInput {
+    pub burn_time_s: f64,
+    pub propellant_mass_kg: f64,
+    pub thrust_n: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExtendedRangeInput {
+    pub name: String,
+    pub range_m: f64,
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Distances {
+    pub haversine_m: f64,
+    pub wgs84_m: f64,
+    /// False when Vincenty did not converge and `wgs84_m` is spherical.
+    pub wgs84_converged: bool,
+    /// `wgs84_m` minus `haversine_m`.
+    pub ellipsoidal_difference_m: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Bearings {
+    pub initial_deg: f64,
+    pub final_deg: f64,
+    pub wgs84_forward_azimuth_deg: f64,
+    pub wgs84_back_azimuth_deg: f64,
+    pub midpoint: LatLon,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct LatLon {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Ranges {
+    pub operational_m: f64,
+    pub published_max_m: f64,
+    /// Closed-form vacuum trajectory at the scenario launch angle, from the
+    /// launch altitude down to the target altitude.
+    pub vacuum_m: f64,
+    /// The same with launch and target at one altitude.
+    pub vacuum_level_m: f64,
+    pub vacuum_flight_time_s: f64,
+    pub vacuum_max_height_m: f64,
+    /// RK4 drag trajectory for `assumptions.earth_model`.
+    pub drag_m: f64,
+    pub drag_flight_time_s: f64,
+    pub drag_max_height_m: f64,
+    pub drag_impact_speed_m_s: f64,
+    pub drag_flat_m: f64,
+    pub drag_spherical_m: f64,
+    /// Where each path meets the terrain, or the target altitude without
+    /// terrain.
+    pub vacuum_ground_impact: GroundImpact,
+    pub drag_ground_impact: GroundImpact,
+    /// End of the boost phase on the drag path, for rockets.
+    pub drag_burnout: Option<Burnout>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct GroundImpact {
+    pub range_m: f64,
+    pub elevation_m: f64,
+    pub time_s: f64,
+    /// Set when a ridge stops the path: where it would otherwise come down.
+    pub unblocked_range_m: Option<f64>,
+}
+
+impl From<ballistics::GroundImpact> for GroundImpact {
+    fn from(impact: ballistics::GroundImpact) -> Self {
+        GroundImpact {
+            range_m: impact.range,
+            elevation_m: impact.elevation,
+            time_s: impact.time,
+            unblocked_range_m: impact.unblocked_range,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Burnout {
+    pub time_s: f64,
+    pub range_m: f64,
+    pub altitude_m: f64,
+    pub speed_m_s: f64,
+}
+
+impl From<ballistics::Burnout> for Burnout {
+    fn from(burnout: ballistics::Burnout) -> Self {
+        Burnout {
+            time_s: burnout.time,
+            range_m: burnout.range,
+            altitude_m: burnout.altitude,
+            speed_m_s: burnout.speed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Assumptions {
+    pub earth_model: EarthModel,
+    pub earth_radius_m: f64,
+    pub gravity_m_s2: f64,
+    pub launch_angle_deg: f64,
+    pub time_step_s: f64,
+    pub atmosphere: Atmosphere,
+}
+
+impl Results {
+    pub fn new(scenario: &Scenario, analysis: &Analysis) -> Self {
+        let weapon = &scenario.weapon;
+        let drag = analysis.drag();
+        Results {
+            schema_version: SCHEMA_VERSION,
+            tool: Tool {
+                name: env!("CARGO_PKG_NAME"),
+                version: env!("CARGO_PKG_VERSION"),
+            },
+            inputs: Inputs {
+                launch: SiteInput::new(&scenario.launch),
+                target: SiteInput::new(&scenario.target),
+                weapon: WeaponInput {
+                    id: weapon.id.clone(),
+                    name: weapon.name.clone(),
+                    category: weapon.category.clone(),
+                    max_range_operational_m: weapon.max_range_operational,
+                    max_range_45deg_m: weapon.max_range_45deg,
+                    extended_ranges: weapon
+                        .extended_ranges
+                        .iter()
+                        .map(|e| ExtendedRangeInput {
+                            name: e.name.clone(),
+                            range_m: e.range,
+                            source: e.source.clone(),
+                        })
+                        .collect(),
+                    projectile_mass_kg: weapon.projectile_mass,
+                    warhead_mass_kg: weapon.warhead_mass,
+                    projectile_length_m: weapon.projectile_length,
+                    calibre_mm: weapon.calibre,
+                    muzzle_velocity_m_s: weapon.muzzle_velocity,
+                    motor: weapon.motor.map(|motor| MotorInput {
+                        burn_time_s: motor.burn_time,
+                        propellant_mass_kg: motor.propellant_mass,
+                        thrust_n: motor.thrust,
+                    }),
+                    sources: weapon.sources.clone(),
+                    references: weapon.references.clone(),
+                },
+            },
+            distances: Distances {
+                haversine_m: analysis.actual_distance,
+                wgs84_m: analysis.ellipsoidal.distance,
+                wgs84_converged: analysis.ellipsoidal.converged,
+                ellipsoidal_difference_m: analysis.ellipsoidal_difference(),
+            },
+            bearings: Bearings {
+                initial_deg: analysis.initial_bearing,
+                final_deg: analysis.final_bearing,
+                wgs84_forward_azimuth_deg: analysis.ellipsoidal.forward_azimuth,
+                wgs84_back_azimuth_deg: analysis.ellipsoidal.back_azimuth,
+                midpoint: LatLon {
+                    lat: analysis.midpoint.0,
+                    lon: analysis.midpoint.1,
+                },
+            },
+            ranges: Ranges {
+                operational_m: weapon.max_range_operational,
+                published_max_m: weapon.max_range_45deg,
+                vacuum_m: analysis.range_theoretical,
+                vacuum_level_m: analysis.trajectory.level_range(),
+                vacuum_flight_time_s: analysis.t_flight,
+                vacuum_max_height_m: analysis.max_h,
+                drag_m: drag.range,
+                drag_flight_time_s: drag.flight_time,
+                drag_max_height_m: drag.max_height,
+                drag_impact_speed_m_s: drag.impact_speed,
+                drag_flat_m: analysis.drag_flat.range,
+                drag_spherical_m: analysis.drag_spherical.range,
+                vacuum_ground_impact: analysis.vacuum_impact.into(),
+                drag_ground_impact: drag.impact().into(),
+                drag_burnout: drag.burnout.map(Burnout::from),
+            },
+            shortfall_m: analysis.range_shortfall,
+            multiplier: analysis.range_multiplier,
+            tiers: analysis.tiers.clone(),
+            verdict: analysis.verdict(),
+            monte_carlo: analysis.monte_carlo.clone(),
+            assumptions: Assumptions {
+                earth_model: analysis.earth_model,
+                earth_radius_m: EARTH_RADIUS,
+                gravity_m_s2: scenario.physics.gravity,
+                launch_angle_deg: scenario.physics.launch_angle,
+                time_step_s: scenario.physics.time_step,
+                atmosphere: scenario.atmosphere,
+            },
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(path, self.to_json()? + "\n")
+            .map_err(|e| format!("cannot write results {}: {}", path.display(), e))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,415 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::atmosphere::Atmosphere;
+use crate::ballistics::Altitudes;
+use crate::coords::{CoordinateError, parse_coordinate, validate_lat_lon};
+use crate::encode::VideoFormat;
+use crate::map::MapOptions;
+use crate::monte_carlo::UncertaintyConfig;
+use crate::terrain::{Srtm, TerrainOptions, TerrainProfile};
+use crate::weapon::{Catalog, DEFAULT_CATALOG_PATH, WeaponSpecs};
+
+pub const DEFAULT_SCENARIO_PATH: &str = "scenarios/default.toml";
+const DEFAULT_SCENARIO: &str = include_str!("../scenarios/default.toml");
+
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    pub launch: Site,
+    pub target: Site,
+    pub weapon: WeaponSpecs,
+    /// Catalog the weapon was selected from, for `select_weapon`.
+    pub catalog: Catalog,
+    pub physics: Physics,
+    pub atmosphere: Atmosphere,
+    pub uncertainty: UncertaintyConfig,
+    pub output: OutputOptions,
+    pub map: MapOptions,
+    pub terrain: Option<TerrainOptions>,
+    /// Terrain between the sites, filled in by `load_terrain`.
+    pub terrain_profile: Option<TerrainProfile>,
+}
+
+/// A scenario as written in the scenario file, with the weapon given as a
+/// catalog id.
+#[derive(Deserialize)]
+struct ScenarioFile {
+    weapon: String,
+    catalog: Option<PathBuf>,
+    launch: Site,
+    target: Site,
+    physics: Physics,
+    #[serde(default)]
+    atmosphere: Atmosphere,
+    #[serde(default)]
+    uncertainty: UncertaintyConfig,
+    output: OutputOptions,
+    #[serde(default)]
+    map: MapOptions,
+    terrain: Option<TerrainOptions>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "SiteSpec")]
+pub struct Site {
+    pub label: String,
+    pub country: String,
+    pub lat: f64,
+    pub lon: f64,
+    /// Radius in meters within which the true position may lie.
+    pub uncertainty: f64,
+    /// Ground height above sea level in meters; read from SRTM when
+    /// `[terrain]` is set and the scenario gives none, else sea level.
+    pub elevation: Option<f64>,
+    /// Where the coordinates come from, printed with the report.
+    pub source: Option<String>,
+}
+
+/// A site as written in the scenario file: either `lat`/`lon` in decimal
+/// degrees or a `position` string in any format `coords::parse_coordinate`
+/// accepts (DMS, UTM, MGRS, Plus Code).
+#[derive(Deserialize)]
+struct SiteSpec {
+    label: String,
+    country: String,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    position: Option<String>,
+    #[serde(default)]
+    uncertainty: f64,
+    elevation: Option<f64>,
+    source: Option<String>,
+}
+
+impl TryFrom<SiteSpec> for Site {
+    type Error = CoordinateError;
+
+    fn try_from(spec: SiteSpec) -> Result<Self, Self::Error> {
+        if spec.uncertainty.is_nan() || spec.uncertainty < 0.0 {
+            return Err(CoordinateError::OutOfRange(format!(
+                "site \"{}\" uncertainty {} must be a non-negative radius",
+                spec.label, spec.uncertainty
+            )));
+        }
+        if spec.elevation.is_some_and(|elevation| !elevation.is_finite()) {
+            return Err(CoordinateError::OutOfRange(format!(
+                "site \"{}\" elevation must be a number of meters",
+                spec.label
+            )));
+        }
+        let (lat, lon) = match (spec.position, spec.lat, spec.lon) {
+            (Some(position), None, None) => parse_coordinate(&position)?,
+            (None, Some(lat), Some(lon)) => validate_lat_lon(lat, lon)?,
+            _ => {
+                return Err(CoordinateError::Malformed(format!(
+                    "site \"{}\" needs either lat and lon or position",
+                    spec.label
+                )));
+            }
+        };
+        Ok(Site {
+            label: spec.label,
+            country: spec.country,
+            lat,
+            lon,
+            uncertainty: spec.uncertainty,
+            elevation: spec.elevation,
+            source: spec.source,
+        })
+    }
+}
+
+impl Site {
+    pub fn set_position(&mut self, position: &str) -> Result<(), CoordinateError> {
+        let (lat, lon) = parse_coordinate(position)?;
+        self.lat = lat;
+        self.lon = lon;
+        // An elevation from the scenario belongs to the old position.
+        self.elevation = None;
+        self.source = Some("command line".to_string());
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Physics {
+    pub gravity: f64,
+    pub launch_angle: f64,
+    #[serde(default = "default_time_step")]
+    pub time_step: f64,
+    #[serde(default)]
+    pub earth_model: EarthModel,
+}
+
+impl Physics {
+    /// Rejects values the trajectories cannot use; a time step of zero
+    /// would never reach the ground.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.time_step.is_finite() && self.time_step > 0.0) {
+            return Err(format!("physics.time_step: {} must be a positive number of seconds", self.time_step));
+        }
+        if !(self.gravity.is_finite() && self.gravity > 0.0) {
+            return Err(format!("physics.gravity: {} must be a positive acceleration", self.gravity));
+        }
+        if !(self.launch_angle > 0.0 && self.launch_angle < 90.0) {
+            return Err(format!(
+                "physics.launch_angle: {} must lie strictly between 0 and 90 degrees",
+                self.launch_angle
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Geometry of the drag trajectory shown in the chart and used as the
+/// drag-limited range. Both are always computed for the report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EarthModel {
+    #[default]
+    Flat,
+    Spherical,
+}
+
+impl EarthModel {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EarthModel::Flat => "flat",
+            EarthModel::Spherical => "round",
+        }
+    }
+}
+
+fn default_time_step() -> f64 {
+    0.01
+}
+
+fn default_width() -> u32 {
+    1920
+}
+
+fn default_height() -> u32 {
+    1080
+}
+
+/// Named frame sizes accepted by `parse_resolution`.
+pub const RESOLUTION_PRESETS: [(&str, u32, u32); 6] = [
+    ("720p", 1280, 720),
+    ("1080p", 1920, 1080),
+    ("1440p", 2560, 1440),
+    ("4k", 3840, 2160),
+    ("vertical", 1080, 1920),
+    ("square", 1080, 1080),
+];
+
+/// Parses a preset name from `RESOLUTION_PRESETS` or `WIDTHxHEIGHT`.
+pub fn parse_resolution(text: &str) -> Result<(u32, u32), String> {
+    let text = text.trim().to_lowercase();
+    if let Some((_, width, height)) = RESOLUTION_PRESETS.iter().find(|(name, _, _)| *name == text) {
+        return Ok((*width, *height));
+    }
+    let invalid = || {
+        let names: Vec<&str> = RESOLUTION_PRESETS.iter().map(|(name, _, _)| *name).collect();
+        format!("invalid resolution \"{}\", use WIDTHxHEIGHT or one of: {}", text, names.join(", "))
+    };
+    let (width, height) = text.split_once('x').ok_or_else(invalid)?;
+    let width = width.trim().parse().map_err(|_| invalid())?;
+    let height = height.trim().parse().map_err(|_| invalid())?;
+    Ok((width, height))
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OutputOptions {
+    /// Where frames are dumped as PNG when `dump_frames` is set.
+    pub frame_dir: String,
+    /// Also write every frame to `frame_dir`, for debugging the renderer.
+    #[serde(default)]
+    pub dump_frames: bool,
+    /// Output file, relative to the output directory. The extension picks
+    /// the format unless `format` is set; no extension means a directory of
+    /// PNG frames.
+    pub video: String,
+    #[serde(default)]
+    pub format: Option<VideoFormat>,
+    /// Frame size in pixels; the layout scales with it.
+    #[serde(default = "default_width")]
+    pub width: u32,
+    #[serde(default = "default_height")]
+    pub height: u32,
+    pub fps: usize,
+    pub duration: usize,
+}
+
+impl OutputOptions {
+    pub fn video_format(&self) -> Result<VideoFormat, String> {
+        match self.format {
+            Some(format) => Ok(format),
+            None => VideoFormat::from_path(Path::new(&self.video)),
+        }
+    }
+
+    pub fn set_resolution(&mut self, text: &str) -> Result<(), String> {
+        (self.width, self.height) = parse_resolution(text)?;
+        self.validate()
+    }
+
+    /// Rejects frame sizes and timings the renderer and encoders cannot use.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width < 320 || self.height < 320 || self.width > 7680 || self.height > 7680 {
+            return Err(format!(
+                "resolution {}x{} out of range, each side must be 320 to 7680 pixels",
+                self.width, self.height
+            ));
+        }
+        if !self.width.is_multiple_of(2) || !self.height.is_multiple_of(2) {
+            return Err(format!(
+                "resolution {}x{} must have even sides for video encoding",
+                self.width, self.height
+            ));
+        }
+        if self.fps == 0 || self.duration == 0 {
+            return Err("fps and duration must be at least 1".to_string());
+        }
+        self.video_format()?;
+        Ok(())
+    }
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read scenario {}: {}", path.display(), e))?;
+        let file: ScenarioFile = toml::from_str(&text)
+            .map_err(|e| format!("invalid scenario {}: {}", path.display(), e))?;
+        let catalog = match &file.catalog {
+            Some(catalog) => Catalog::load(catalog)?,
+            None if Path::new(DEFAULT_CATALOG_PATH).exists() => {
+                Catalog::load(Path::new(DEFAULT_CATALOG_PATH))?
+            }
+            None => Catalog::default(),
+        };
+        Self::resolve(file, catalog)
+            .map_err(|e| format!("invalid scenario {}: {}", path.display(), e).into())
+    }
+
+    /// Parses a scenario, looking its weapon up in `catalog`.
+    pub fn parse(text: &str, catalog: Catalog) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self::resolve(toml::from_str(text)?, catalog)?)
+    }
+
+    fn resolve(file: ScenarioFile, catalog: Catalog) -> Result<Self, String> {
+        let weapon = catalog.get(&file.weapon)?.clone();
+        file.physics.validate()?;
+        file.output.validate()?;
+        file.uncertainty.validate()?;
+        Ok(Scenario {
+            launch: file.launch,
+            target: file.target,
+            weapon,
+            catalog,
+            physics: file.physics,
+            atmosphere: file.atmosphere,
+            uncertainty: file.uncertainty,
+            output: file.output,
+            map: file.map,
+            terrain: file.terrain,
+            terrain_profile: None,
+        })
+    }
+
+    /// Samples the terrain between the sites from the SRTM tiles in
+    /// `[terrain]` and reads site elevations the scenario does not give.
+    /// Call again after moving a site.
+    pub fn load_terrain(&mut self) -> Result<(), String> {
+        let Some(options) = &self.terrain else {
+            return Ok(());
+        };
+        let mut srtm = Srtm::new(&options.dir);
+        let profile = TerrainProfile::sample(&mut srtm, &self.launch, &self.target, options.samples)?;
+        for site in [&mut self.launch, &mut self.target] {
+            if site.elevation.is_none() {
+                let elevation = srtm.elevation(site.lat, site.lon)?.ok_or_else(|| {
+                    format!(
+                        "no SRTM elevation for {} in {}, add {} or set elevation in the scenario",
+                        site.label,
+                        options.dir.display(),
+                        crate::terrain::tile_name(site.lat, site.lon)
+                    )
+                })?;
+                site.elevation = Some(elevation);
+            }
+        }
+        self.terrain_profile = Some(profile);
+        Ok(())
+    }
+
+    /// Site elevations, sea level where the scenario gives none.
+    pub fn altitudes(&self) -> Altitudes {
+        Altitudes {
+            launch: self.launch.elevation.unwrap_or(0.0),
+            impact: self.target.elevation.unwrap_or(0.0),
+        }
+    }
+
+    /// Whether any input is uncertain: a site radius or a weapon
+    /// distribution. Without one the verdict is deterministic and the Monte
+    /// Carlo analysis is skipped.
+    pub fn has_uncertainty(&self) -> bool {
+        self.launch.uncertainty > 0.0
+            || self.target.uncertainty > 0.0
+            || self.uncertainty.max_range_operational.is_some()
+            || self.uncertainty.muzzle_velocity.is_some()
+    }
+
+    /// Switches to another weapon from the catalog. Weapon distributions in
+    /// `[uncertainty]` describe the scenario's own weapon, so they are
+    /// dropped and the new weapon's figures are held fixed.
+    pub fn select_weapon(&mut self, id: &str) -> Result<(), String> {
+        if id == self.weapon.id {
+            return Ok(());
+        }
+        self.weapon = self.catalog.get(id)?.clone();
+        self.uncertainty.max_range_operational = None;
+        self.uncertainty.muzzle_velocity = None;
+        Ok(())
+    }
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self::parse(DEFAULT_SCENARIO, Catalog::default())
+            .expect("bundled default scenario is valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_physics(line: &str, value: &str) -> Result<Scenario, Box<dyn std::error::Error>> {
+        let start = DEFAULT_SCENARIO.find(line).expect("line in the default scenario");
+        let end = start + DEFAULT_SCENARIO[start..].find('\n').unwrap();
+        let text = format!("{}{} = {}{}", &DEFAULT_SCENARIO[..start], line, value, &DEFAULT_SCENARIO[end..]);
+        Scenario::parse(&text, Catalog::default())
+    }
+
+    #[test]
+    fn physics_outside_the_trajectory_model_is_rejected() {
+        for (line, value) in [
+            ("time_step", "0.0"),
+            ("time_step", "-0.01"),
+            ("time_step", "nan"),
+            ("time_step", "inf"),
+            ("gravity", "0.0"),
+            ("gravity", "-9.81"),
+            ("launch_angle", "0.0"),
+            ("launch_angle", "90.0"),
+            ("launch_angle", "120.0"),
+        ] {
+            let error = with_physics(line, value).expect_err(&format!("{} = {} accepted", line, value));
+            assert!(error.to_string().starts_with(&format!("physics.{}: ", line)), "{}", error);
+        }
+        assert!(with_physics("launch_angle", "30.0").is_ok());
+    }
+}
@@ -0,0 +1,323 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::map::great_circle;
+use crate::scenario::Site;
+
+/// `[terrain]`: SRTM tiles for site elevations and the terrain profile.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TerrainOptions {
+    /// Directory of `.hgt` tiles named after their south-west corner,
+    /// e.g. `N14E103.hgt`.
+    pub dir: PathBuf,
+    /// Points sampled along the great circle from launch to target.
+    #[serde(default = "default_samples")]
+    pub samples: usize,
+}
+
+fn default_samples() -> usize {
+    500
+}
+
+/// Marks a missing sample in SRTM data.
+const VOID: i16 = -32768;
+
+/// One 1°x1° tile: big-endian 16-bit heights in meters, rows from north to
+/// south, with the edge rows and columns shared with the neighbours.
+struct HgtTile {
+    /// Samples per side, 1201 for 3 arc-second or 3601 for 1 arc-second.
+    size: usize,
+    heights: Vec<i16>,
+}
+
+impl HgtTile {
+    fn load(path: &Path) -> Result<Self, String> {
+        let bytes = fs::read(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        let size = match bytes.len() {
+            2_884_802 => 1201,
+            25_934_402 => 3601,
+            n => {
+                return Err(format!(
+                    "{} is {} bytes, not a 1 or 3 arc-second SRTM tile",
+                    path.display(),
+                    n
+                ));
+            }
+        };
+        let heights = bytes.chunks_exact(2).map(|b| i16::from_be_bytes([b[0], b[1]])).collect();
+        Ok(HgtTile { size, heights })
+    }
+
+    /// Bilinear interpolation at a position within the tile, in degrees
+    /// north and east of its south-west corner. Void samples are left out.
+    fn elevation(&self, north: f64, east: f64) -> Option<f64> {
+        let last = (self.size - 1) as f64;
+        let row = ((1.0 - north) * last).clamp(0.0, last);
+        let column = (east * last).clamp(0.0, last);
+        let (r0, c0) = (row.floor() as usize, column.floor() as usize);
+        let (r1, c1) = ((r0 + 1).min(self.size - 1), (c0 + 1).min(self.size - 1));
+        let (fr, fc) = (row - r0 as f64, column - c0 as f64);
+        let corners = [
+            (r0, c0, (1.0 - fr) * (1.0 - fc)),
+            (r0, c1, (1.0 - fr) * fc),
+            (r1, c0, fr * (1.0 - fc)),
+            (r1, c1, fr * fc),
+        ];
+        let (sum, weight) = corners
+            .iter()
+            .filter_map(|&(r, c, w)| {
+                let height = self.heights[r * self.size + c];
+                (height != VOID).then_some((height as f64 * w, w))
+            })
+            .fold((0.0, 0.0), |(s, t), (h, w)| (s + h, t + w));
+        (weight > 1e-9).then(|| sum / weight)
+    }
+}
+
+/// Reads elevations from a directory of SRTM `.hgt` tiles, loading each
+/// tile on first use.
+pub struct Srtm {
+    dir: PathBuf,
+    tiles: HashMap<(i32, i32), Option<HgtTile>>,
+}
+
+/// File name of the tile containing `(lat, lon)`, e.g. `N14E103.hgt`.
+pub fn tile_name(lat: f64, lon: f64) -> String {
+    let (lat0, lon0) = (lat.floor() as i32, lon.floor() as i32);
+    format!(
+        "{}{:02}{}{:03}.hgt",
+        if lat0 >= 0 { 'N' } else { 'S' },
+        lat0.abs(),
+        if lon0 >= 0 { 'E' } else { 'W' },
+        lon0.abs()
+    )
+}
+
+impl Srtm {
+    pub fn new(dir: &Path) -> Self {
+        Srtm {
+            dir: dir.to_path_buf(),
+            tiles: HashMap::new(),
+        }
+    }
+
+    /// Elevation above sea level in meters, or `None` where the tile is
+    /// missing (SRTM has no tiles over open sea) or the data is void.
+    pub fn elevation(&mut self, lat: f64, lon: f64) -> Result<Option<f64>, String> {
+        let key = (lat.floor() as i32, lon.floor() as i32);
+        if !self.tiles.contains_key(&key) {
+            let name = tile_name(lat, lon);
+            let path = [name.clone(), name.to_lowercase()]
+                .iter()
+                .map(|name| self.dir.join(name))
+                .find(|path| path.exists());
+            let tile = path.map(|path| HgtTile::load(&path)).transpose()?;
+            self.tiles.insert(key, tile);
+        }
+        Ok(self.tiles[&key]
+            .as_ref()
+            .and_then(|tile| tile.elevation(lat - key.0 as f64, lon - key.1 as f64)))
+    }
+}
+
+/// Terrain heights along the great circle from launch to target.
+#[derive(Debug, Clone)]
+pub struct TerrainProfile {
+    /// `(distance from launch, elevation)` in meters, evenly spaced. Gaps
+    /// in the data are filled linearly from the neighbouring samples.
+    pub points: Vec<(f64, f64)>,
+}
+
+impl TerrainProfile {
+    /// Samples `samples` points between the sites, endpoints included.
+    pub fn sample(srtm: &mut Srtm, launch: &Site, target: &Site, samples: usize) -> Result<Self, String> {
+        let samples = samples.max(2);
+        let path = great_circle(launch.lat, launch.lon, target.lat, target.lon, samples - 1);
+        let distance = crate::geo::haversine_distance(launch.lat, launch.lon, target.lat, target.lon);
+        let heights = path
+            .iter()
+            .map(|&(lat, lon)| srtm.elevation(lat, lon))
+            .collect::<Result<Vec<_>, _>>()?;
+        let known: Vec<usize> = (0..heights.len()).filter(|&i| heights[i].is_some()).collect();
+        if known.is_empty() {
+            return Err(format!(
+                "no SRTM data between {} and {}, tiles such as {} are missing",
+                launch.label,
+                target.label,
+                tile_name(launch.lat, launch.lon)
+            ));
+        }
+        let points = heights
+            .iter()
+            .enumerate()
+            .map(|(i, height)| {
+                let elevation = height.unwrap_or_else(|| {
+                    let before = known.iter().rev().find(|&&k| k < i);
+                    let after = known.iter().find(|&&k| k > i);
+                    match (before, after) {
+                        (Some(&a), Some(&b)) => {
+                            let (ha, hb) = (heights[a].unwrap_or(0.0), heights[b].unwrap_or(0.0));
+                            ha + (hb - ha) * (i - a) as f64 / (b - a) as f64
+                        }
+                        (Some(&k), None) | (None, Some(&k)) => heights[k].unwrap_or(0.0),
+                        (None, None) => 0.0,
+                    }
+                });
+                (distance * i as f64 / (samples - 1) as f64, elevation)
+            })
+            .collect();
+        Ok(TerrainProfile { points })
+    }
+
+    /// Elevation at `distance` meters from launch, linearly interpolated.
+    pub fn elevation_at(&self, distance: f64) -> f64 {
+        let points = &self.points;
+        match points.iter().position(|&(d, _)| d >= distance) {
+            None => points.last().map_or(0.0, |&(_, h)| h),
+            Some(0) => points[0].1,
+            Some(i) => {
+                let (d0, h0) = points[i - 1];
+                let (d1, h1) = points[i];
+                h0 + (h1 - h0) * (distance - d0) / (d1 - d0)
+            }
+        }
+    }
+
+    /// The highest point as `(distance, elevation)`.
+    pub fn highest(&self) -> (f64, f64) {
+        self.points
+            .iter()
+            .copied()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((0.0, 0.0))
+    }
+
+    /// The lowest point as `(distance, elevation)`.
+    pub fn lowest(&self) -> (f64, f64) {
+        self.points
+            .iter()
+            .copied()
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((0.0, 0.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenario::Scenario;
+
+    /// A scratch directory for tiles, unique to the test.
+    fn tile_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bm21-terrain-{}-{}", test, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Writes a `size`x`size` tile with heights from `height(row, column)`.
+    fn write_tile(dir: &Path, name: &str, size: usize, height: impl Fn(usize, usize) -> i16) {
+        let bytes: Vec<u8> = (0..size * size)
+            .flat_map(|i| height(i / size, i % size).to_be_bytes())
+            .collect();
+        fs::write(dir.join(name), bytes).unwrap();
+    }
+
+    fn site(lat: f64, lon: f64) -> Site {
+        Site {
+            lat,
+            lon,
+            elevation: None,
+            ..Scenario::default().launch
+        }
+    }
+
+    #[test]
+    fn load_tells_the_grid_from_the_file_size() {
+        let dir = tile_dir("load");
+        for size in [1201, 3601] {
+            let name = format!("N00E{:03}.hgt", size % 1000);
+            write_tile(&dir, &name, size, |_, _| 0);
+            assert_eq!(HgtTile::load(&dir.join(&name)).unwrap().size, size);
+        }
+        fs::write(dir.join("N00E000.hgt"), vec![0u8; 1200 * 1200 * 2]).unwrap();
+        let error = HgtTile::load(&dir.join("N00E000.hgt")).err().unwrap();
+        assert!(error.ends_with("is 2880000 bytes, not a 1 or 3 arc-second SRTM tile"), "{}", error);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn elevation_interpolates_with_the_north_row_first() {
+        let tile = HgtTile {
+            size: 3,
+            heights: vec![200, 210, 220, 100, 110, 120, 0, 10, 20],
+        };
+        assert_eq!(tile.elevation(0.0, 0.0), Some(0.0));
+        assert_eq!(tile.elevation(0.0, 1.0), Some(20.0));
+        assert_eq!(tile.elevation(1.0, 0.0), Some(200.0));
+        assert_eq!(tile.elevation(1.0, 1.0), Some(220.0));
+        assert_eq!(tile.elevation(0.5, 0.5), Some(110.0));
+        assert_eq!(tile.elevation(0.75, 0.25), Some(155.0));
+        assert_eq!(tile.elevation(0.25, 0.75), Some(65.0));
+    }
+
+    #[test]
+    fn void_samples_are_left_out_of_the_interpolation() {
+        let tile = HgtTile {
+            size: 2,
+            heights: vec![VOID, 10, 20, 30],
+        };
+        // The remaining three corners share the weight equally.
+        assert_eq!(tile.elevation(0.5, 0.5), Some(20.0));
+        assert_eq!(tile.elevation(0.0, 0.5), Some(25.0));
+        assert_eq!(tile.elevation(1.0, 0.0), None);
+    }
+
+    #[test]
+    fn tile_names_follow_the_south_west_corner() {
+        assert_eq!(tile_name(14.36, 103.26), "N14E103.hgt");
+        assert_eq!(tile_name(-33.9, -70.6), "S34W071.hgt");
+        assert_eq!(tile_name(0.5, -0.5), "N00W001.hgt");
+        assert_eq!(tile_name(-0.5, 0.5), "S01E000.hgt");
+    }
+
+    #[test]
+    fn profile_fills_voids_and_missing_tiles() {
+        let dir = tile_dir("profile");
+        // Heights rise 1 m per column eastward, with a void band in the middle.
+        write_tile(&dir, "N14E103.hgt", 1201, |_, column| {
+            if (500..=700).contains(&column) { VOID } else { column as i16 }
+        });
+        let mut srtm = Srtm::new(&dir);
+        let profile = TerrainProfile::sample(&mut srtm, &site(14.5, 103.2), &site(14.5, 104.5), 131).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let path = great_circle(14.5, 103.2, 14.5, 104.5, 130);
+        let last_known = path.iter().rposition(|&(_, lon)| lon < 104.0).unwrap();
+        for (i, (&(_, lon), &(_, elevation))) in path.iter().zip(&profile.points).enumerate() {
+            if i <= last_known {
+                // Across the void the fill follows the slope either side.
+                assert!((elevation - (lon - 103.0) * 1200.0).abs() < 2.0, "{} {}", lon, elevation);
+            } else {
+                // Past the last tile the last known height is held.
+                assert_eq!(elevation, profile.points[last_known].1);
+            }
+        }
+
+        let nothing = TerrainProfile::sample(&mut srtm, &site(-10.5, 20.5), &site(-10.2, 20.8), 10);
+        assert!(nothing.err().unwrap().contains("S11E020.hgt"));
+    }
+
+    #[test]
+    fn elevation_at_interpolates_and_holds_the_ends() {
+        let profile = TerrainProfile {
+            points: vec![(0.0, 10.0), (100.0, 30.0), (200.0, 0.0)],
+        };
+        assert_eq!(profile.elevation_at(50.0), 20.0);
+        assert_eq!(profile.elevation_at(100.0), 30.0);
+        assert_eq!(profile.elevation_at(150.0), 15.0);
+        assert_eq!(profile.elevation_at(-10.0), 10.0);
+        assert_eq!(profile.elevation_at(300.0), 0.0);
+    }
+}
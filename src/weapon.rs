@@ -0,0 +1,378 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::ballistics::RocketMotor;
+
+pub const DEFAULT_CATALOG_PATH: &str = "catalog/weapons.toml";
+const DEFAULT_CATALOG: &str = include_str!("../catalog/weapons.toml");
+
+/// Placeholder printed for a figure without a citation.
+pub const UNSOURCED: &str = "unsourced";
+
+#[derive(Debug, Clone)]
+pub struct WeaponSpecs {
+    pub id: String,
+    pub name: String,
+    pub category: String,
+    pub description: String,
+    /// Nominal range in meters, what the system is typically employed at.
+    pub max_range_operational: f64,
+    /// Published maximum range in meters.
+    pub max_range_45deg: f64,
+    pub projectile_mass: f64,
+    pub warhead_mass: Option<f64>,
+    pub projectile_length: Option<f64>,
+    /// Calibre in millimeters.
+    pub calibre: f64,
+    pub muzzle_velocity: f64,
+    /// Boost phase for rockets; the drag model flies it instead of starting
+    /// at `muzzle_velocity`.
+    pub motor: Option<RocketMotor>,
+    /// `[Mach, Cd]` pairs for the drag model, sorted by Mach.
+    pub drag_coefficients: Vec<(f64, f64)>,
+    /// Longer-range munitions or variants beyond the published maximum.
+    pub extended_ranges: Vec<ExtendedRange>,
+    /// Source key per field, e.g. `"muzzle_velocity" -> "janes_iw"`.
+    pub sources: BTreeMap<String, String>,
+    /// Full citation per source key used by this weapon.
+    pub references: BTreeMap<String, String>,
+}
+
+impl WeaponSpecs {
+    /// Source key cited for `field`.
+    pub fn source(&self, field: &str) -> &str {
+        self.sources.get(field).map(String::as_str).unwrap_or(UNSOURCED)
+    }
+
+    /// Whether the catalog cites a source for `field`.
+    pub fn is_sourced(&self, field: &str) -> bool {
+        self.sources.contains_key(field)
+    }
+
+    /// Drag model inputs the catalog gives as estimates, without a source:
+    /// the drag curve and, for a rocket, the motor.
+    pub fn unsourced_drag_inputs(&self) -> Vec<&'static str> {
+        let mut inputs = Vec::new();
+        if !self.is_sourced("drag_coefficients") {
+            inputs.push("drag curve");
+        }
+        if self.motor.is_some() && MOTOR_FIELDS.iter().any(|field| !self.is_sourced(field)) {
+            inputs.push("rocket motor");
+        }
+        inputs
+    }
+
+    /// Whether the air-drag range rests only on sourced figures, and so
+    /// counts toward the verdict.
+    pub fn drag_model_is_sourced(&self) -> bool {
+        self.unsourced_drag_inputs().is_empty()
+    }
+
+    /// Full citation for `field`.
+    pub fn citation(&self, field: &str) -> &str {
+        self.references
+            .get(self.source(field))
+            .map(String::as_str)
+            .unwrap_or(UNSOURCED)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExtendedRange {
+    pub name: String,
+    /// Range in meters.
+    #[serde(rename = "value")]
+    pub range: f64,
+    /// Source key, see `WeaponSpecs::references`.
+    pub source: String,
+}
+
+/// A catalog value and the key of the source it was taken from.
+#[derive(Deserialize)]
+struct Sourced<T> {
+    value: T,
+    source: String,
+}
+
+/// A drag table or motor figure, which unlike the other figures may be
+/// given without a source; the air-drag range is then shown but left out
+/// of the verdict.
+#[derive(Deserialize)]
+struct Estimate<T> {
+    value: T,
+    source: Option<String>,
+}
+
+impl<T> From<Sourced<T>> for Estimate<T> {
+    fn from(sourced: Sourced<T>) -> Self {
+        Estimate {
+            value: sourced.value,
+            source: Some(sourced.source),
+        }
+    }
+}
+
+/// Catalog fields describing the rocket motor.
+const MOTOR_FIELDS: [&str; 3] = ["burn_time", "propellant_mass", "thrust"];
+
+/// A weapon as written in the catalog file.
+#[derive(Deserialize)]
+struct WeaponEntry {
+    id: String,
+    name: String,
+    category: String,
+    #[serde(default)]
+    description: String,
+    max_range_operational: Sourced<f64>,
+    max_range_45deg: Sourced<f64>,
+    projectile_mass: Sourced<f64>,
+    warhead_mass: Option<Sourced<f64>>,
+    projectile_length: Option<Sourced<f64>>,
+    calibre: Sourced<f64>,
+    muzzle_velocity: Sourced<f64>,
+    burn_time: Option<Estimate<f64>>,
+    propellant_mass: Option<Estimate<f64>>,
+    thrust: Option<Estimate<f64>>,
+    drag_coefficients: Option<Estimate<Vec<(f64, f64)>>>,
+    #[serde(default)]
+    extended_range: Vec<ExtendedRange>,
+}
+
+#[derive(Deserialize)]
+struct CatalogFile {
+    sources: BTreeMap<String, String>,
+    weapons: Vec<WeaponEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Catalog {
+    pub weapons: Vec<WeaponSpecs>,
+}
+
+impl Catalog {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read weapon catalog {}: {}", path.display(), e))?;
+        Self::parse(&text)
+            .map_err(|e| format!("invalid weapon catalog {}: {}", path.display(), e).into())
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let file: CatalogFile = toml::from_str(text)?;
+        let mut weapons = Vec::with_capacity(file.weapons.len());
+        for entry in file.weapons {
+            if weapons.iter().any(|w: &WeaponSpecs| w.id == entry.id) {
+                return Err(format!("duplicate weapon id \"{}\"", entry.id).into());
+            }
+            weapons.push(Self::resolve(entry, &file.sources)?);
+        }
+        Ok(Catalog { weapons })
+    }
+
+    /// Checks every cited key against `[sources]` and flattens the entry.
+    fn resolve(
+        entry: WeaponEntry,
+        citations: &BTreeMap<String, String>,
+    ) -> Result<WeaponSpecs, String> {
+        let mut sources = BTreeMap::new();
+        let mut references = BTreeMap::new();
+        let mut cite = |field: &str, source: String| -> Result<(), String> {
+            let citation = citations.get(&source).ok_or_else(|| {
+                format!(
+                    "weapon \"{}\" field {} cites unknown source \"{}\"",
+                    entry.id, field, source
+                )
+            })?;
+            references.insert(source.clone(), citation.clone());
+            sources.insert(field.to_string(), source);
+            Ok(())
+        };
+
+        let mut value = |field: &str, estimate: Estimate<f64>| -> Result<f64, String> {
+            if !estimate.value.is_finite() || estimate.value <= 0.0 {
+                return Err(format!(
+                    "weapon \"{}\" field {} must be positive, got {}",
+                    entry.id, field, estimate.value
+                ));
+            }
+            if let Some(source) = estimate.source {
+                cite(field, source)?;
+            }
+            Ok(estimate.value)
+        };
+        let max_range_operational = value("max_range_operational", entry.max_range_operational.into())?;
+        let max_range_45deg = value("max_range_45deg", entry.max_range_45deg.into())?;
+        let projectile_mass = value("projectile_mass", entry.projectile_mass.into())?;
+        let warhead_mass = entry.warhead_mass.map(|v| value("warhead_mass", v.into())).transpose()?;
+        let projectile_length = entry
+            .projectile_length
+            .map(|v| value("projectile_length", v.into()))
+            .transpose()?;
+        let calibre = value("calibre", entry.calibre.into())?;
+        let muzzle_velocity = value("muzzle_velocity", entry.muzzle_velocity.into())?;
+        let motor = match (entry.burn_time, entry.propellant_mass, entry.thrust) {
+            (Some(burn_time), Some(propellant_mass), Some(thrust)) => Some(RocketMotor {
+                burn_time: value("burn_time", burn_time)?,
+                propellant_mass: value("propellant_mass", propellant_mass)?,
+                thrust: value("thrust", thrust)?,
+            }),
+            (None, None, None) => None,
+            _ => {
+                return Err(format!(
+                    "weapon \"{}\" needs all of burn_time, propellant_mass and thrust for a motor",
+                    entry.id
+                ));
+            }
+        };
+        if let Some(motor) = motor.filter(|motor| motor.propellant_mass >= projectile_mass) {
+            return Err(format!(
+                "weapon \"{}\" propellant_mass {} must be less than projectile_mass {}",
+                entry.id, motor.propellant_mass, projectile_mass
+            ));
+        }
+
+        let drag_coefficients = match entry.drag_coefficients {
+            Some(table) => {
+                if let Some(source) = table.source {
+                    cite("drag_coefficients", source)?;
+                }
+                table.value
+            }
+            None => default_drag_coefficients(),
+        };
+
+        for extended in &entry.extended_range {
+            if !extended.range.is_finite() || extended.range <= 0.0 {
+                return Err(format!(
+                    "weapon \"{}\" extended range \"{}\" must be positive, got {}",
+                    entry.id, extended.name, extended.range
+                ));
+            }
+            let citation = citations.get(&extended.source).ok_or_else(|| {
+                format!(
+                    "weapon \"{}\" extended range \"{}\" cites unknown source \"{}\"",
+                    entry.id, extended.name, extended.source
+                )
+            })?;
+            references.insert(extended.source.clone(), citation.clone());
+        }
+
+        Ok(WeaponSpecs {
+            id: entry.id,
+            name: entry.name,
+            category: entry.category,
+            description: entry.description,
+            max_range_operational,
+            max_range_45deg,
+            projectile_mass,
+            warhead_mass,
+            projectile_length,
+            calibre,
+            muzzle_velocity,
+            motor,
+            drag_coefficients,
+            extended_ranges: entry.extended_range,
+            sources,
+            references,
+        })
+    }
+
+    pub fn get(&self, id: &str) -> Result<&WeaponSpecs, String> {
+        self.weapons.iter().find(|w| w.id == id).ok_or_else(|| {
+            let ids: Vec<&str> = self.weapons.iter().map(|w| w.id.as_str()).collect();
+            format!("unknown weapon \"{}\", catalog has: {}", id, ids.join(", "))
+        })
+    }
+}
+
+impl Default for Catalog {
+    fn default() -> Self {
+        Self::parse(DEFAULT_CATALOG).expect("bundled weapon catalog is valid")
+    }
+}
+
+/// Generic fin-stabilised rocket drag curve, used when a weapon declares none.
+pub fn default_drag_coefficients() -> Vec<(f64, f64)> {
+    vec![
+        (0.0, 0.30),
+        (0.8, 0.32),
+        (1.0, 0.45),
+        (1.2, 0.48),
+        (1.5, 0.42),
+        (2.0, 0.36),
+        (2.5, 0.32),
+        (3.0, 0.29),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalog(drag_coefficients: &str) -> Result<Catalog, Box<dyn std::error::Error>> {
+        Catalog::parse(&format!(
+            r#"
+            [sources]
+            weg = "US Army TRADOC Worldwide Equipment Guide"
+
+            [[weapons]]
+            id = "test"
+            name = "Test"
+            category = "gun"
+            max_range_operational = {{ value = 10000.0, source = "weg" }}
+            max_range_45deg = {{ value = 12000.0, source = "weg" }}
+            projectile_mass = {{ value = 20.0, source = "weg" }}
+            calibre = {{ value = 122.0, source = "weg" }}
+            muzzle_velocity = {{ value = 500.0, source = "weg" }}
+            {}
+            "#,
+            drag_coefficients
+        ))
+    }
+
+    #[test]
+    fn drag_table_source_is_optional() {
+        let cited = catalog(r#"drag_coefficients = { value = [[0.0, 0.2]], source = "weg" }"#).unwrap();
+        assert!(cited.weapons[0].is_sourced("drag_coefficients"));
+        assert_eq!(cited.weapons[0].citation("drag_coefficients"), "US Army TRADOC Worldwide Equipment Guide");
+
+        let estimated = catalog("drag_coefficients = { value = [[0.0, 0.2]] }").unwrap();
+        assert!(!estimated.weapons[0].is_sourced("drag_coefficients"));
+        assert_eq!(estimated.weapons[0].source("drag_coefficients"), UNSOURCED);
+        assert_eq!(estimated.weapons[0].drag_coefficients, vec![(0.0, 0.2)]);
+
+        let error = catalog(r#"drag_coefficients = { value = [[0.0, 0.2]], source = "nope" }"#).unwrap_err();
+        assert!(error.to_string().contains("unknown source \"nope\""), "{}", error);
+    }
+
+    #[test]
+    fn an_unsourced_motor_leaves_the_drag_model_unsourced() {
+        let table = r#"drag_coefficients = { value = [[0.0, 0.2]], source = "weg" }"#;
+        let motor = |thrust_source: &str| {
+            format!(
+                r#"{}
+                burn_time = {{ value = 1.5, source = "weg" }}
+                propellant_mass = {{ value = 5.0, source = "weg" }}
+                thrust = {{ value = 9000.0{} }}"#,
+                table, thrust_source
+            )
+        };
+
+        let gun = catalog(table).unwrap();
+        assert!(gun.weapons[0].drag_model_is_sourced());
+        let cited = catalog(&motor(r#", source = "weg""#)).unwrap();
+        assert!(cited.weapons[0].drag_model_is_sourced());
+
+        let estimated = catalog(&motor("")).unwrap();
+        let weapon = &estimated.weapons[0];
+        assert_eq!(weapon.motor.unwrap().thrust, 9000.0);
+        assert_eq!(weapon.source("thrust"), UNSOURCED);
+        assert_eq!(weapon.unsourced_drag_inputs(), vec!["rocket motor"]);
+        assert!(!weapon.drag_model_is_sourced());
+
+        let error = catalog(&motor("").replace("9000.0", "-1.0")).unwrap_err();
+        assert!(error.to_string().contains("field thrust must be positive"), "{}", error);
+    }
+}